@@ -0,0 +1,64 @@
+use cangjie_card::response::{
+    apply_cors, compress_response, create_error_response, create_preflight_response,
+    create_response,
+};
+use cangjie_card::rules::available_analyzers;
+use cangjie_card::utils::{ensure_cjlint_extracted, init_tracing};
+use tracing::error;
+use http::Method;
+use vercel_runtime::{run, Body, Error, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    init_tracing();
+    if let Err(e) = ensure_cjlint_extracted().await {
+        error!("Failed to extract cjlint: {}", e);
+        return Err(Error::from(e));
+    }
+
+    run(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    if req.method() == Method::OPTIONS {
+        return create_preflight_response(&req);
+    }
+
+    let response = match available_analyzers().await {
+        Ok(analyzers) => create_response(StatusCode::OK, true, None, Some(analyzers), None)?,
+        Err(e) => {
+            error!("Failed to read analyzer list: {}", e);
+            create_error_response(&e)?
+        }
+    };
+    let response = compress_response(&req, response)?;
+    Ok(apply_cors(&req, response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cangjie_card::test_support::set_env;
+
+    #[tokio::test]
+    async fn analyzers_are_listed_after_extraction() {
+        let home = tempfile::tempdir().unwrap();
+        let home_path = home.path().to_string_lossy().to_string();
+        let _env = set_env(&[("CANGJIE_HOME", Some(home_path.as_str()))]).await;
+        ensure_cjlint_extracted().await.unwrap();
+
+        let req = http::Request::builder()
+            .uri("/api/analyzers")
+            .body(Body::Empty)
+            .unwrap();
+        let response = handler(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value = match response.body() {
+            Body::Text(json) => serde_json::from_str(json).unwrap(),
+            _ => panic!("analyzers should return JSON"),
+        };
+        let analyzers = body["data"].as_array().unwrap();
+        assert!(!analyzers.is_empty());
+        assert!(analyzers.iter().all(|analyzer| analyzer["name"].as_str().is_some()));
+    }
+}
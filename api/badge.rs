@@ -0,0 +1,95 @@
+use cangjie_card::badge::render_badge;
+use cangjie_card::error::RefreshError;
+use cangjie_card::models::AnalysisResult;
+use cangjie_card::response::{compress_response, create_error_response};
+use cangjie_card::storage::get_from_redis;
+use cangjie_card::utils::{init_tracing, query_params};
+use http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use tracing::warn;
+use vercel_runtime::{run, Body, Error, Request, Response, StatusCode};
+
+// 徽章只做短时间缓存，刷新后能较快看到新的结果
+const BADGE_CACHE_CONTROL: &str = "public, max-age=300";
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    init_tracing();
+    run(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    let response = handle(&req).await?;
+    compress_response(&req, response)
+}
+
+async fn handle(req: &Request) -> Result<Response<Body>, Error> {
+    let hash_query = query_params(req);
+    let repo = match hash_query.get("repo") {
+        Some(repo) => repo,
+        None => return create_error_response(&RefreshError::MissingRepoParam),
+    };
+
+    // 读取失败时同样显示 unknown，避免 README 中出现破损的图片
+    let counts = match get_from_redis(repo).await {
+        Ok(Some(content)) => serde_json::from_str::<AnalysisResult>(&content)
+            .ok()
+            .map(|result| (result.summary.total, result.summary.mandatory)),
+        Ok(None) => None,
+        Err(e) => {
+            warn!("Failed to read cached analysis: {}", e);
+            None
+        }
+    };
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "image/svg+xml")
+        .header(CACHE_CONTROL, BADGE_CACHE_CONTROL)
+        .body(Body::from(render_badge(counts)))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cangjie_card::storage::save_to_redis;
+    use cangjie_card::test_support::{fake_redis, result};
+
+    async fn badge(repo: &str) -> String {
+        let req = http::Request::builder()
+            .uri(format!("/api/badge?repo={}", repo))
+            .body(Body::Empty)
+            .unwrap();
+        let response = handler(req).await.unwrap();
+        assert_eq!(response.headers()[CONTENT_TYPE], "image/svg+xml");
+        match response.body() {
+            Body::Text(svg) => svg.clone(),
+            _ => panic!("badge should be an SVG document"),
+        }
+    }
+
+    #[tokio::test]
+    async fn badge_color_follows_mandatory_findings() {
+        fake_redis().await;
+        for (mandatory, color) in [(0, "#4c1"), (10, "#dfb317"), (11, "#e05d44")] {
+            let repo = format!("https://github.com/demo/badge_{}", mandatory);
+            let mut analysis_result = result(Vec::new());
+            analysis_result.summary.total = mandatory + 2;
+            analysis_result.summary.mandatory = mandatory;
+            save_to_redis(&repo, &serde_json::to_string(&analysis_result).unwrap())
+                .await
+                .unwrap();
+
+            let svg = badge(&repo).await;
+            assert!(svg.contains(&format!("fill=\"{}\"", color)), "{}", svg);
+            assert!(svg.contains(&format!("cjlint: {} issues", mandatory + 2)));
+        }
+    }
+
+    #[tokio::test]
+    async fn missing_result_shows_unknown() {
+        fake_redis().await;
+        let svg = badge("https://github.com/demo/badge_missing").await;
+        assert!(svg.contains("cjlint: unknown"));
+        assert!(svg.contains("fill=\"#9f9f9f\""));
+    }
+}
@@ -0,0 +1,247 @@
+use cangjie_card::analysis::parse_extra_args;
+use cangjie_card::config::{
+    batch_concurrency, batch_max_repos, cjlint_extra_args, request_timeout_seconds,
+};
+use cangjie_card::error::RefreshError;
+use cangjie_card::models::{AnalysisResult, BatchEntry, CloneOptions, HistoryEntry};
+use cangjie_card::pipeline::{load_or_analyze, AnalyzeFlags};
+use cangjie_card::rate_limit::{check_rate_limit, client_ip};
+use cangjie_card::repository::{normalize_repo_input, sweep_stale_repos, validate_repo_url};
+use cangjie_card::response::{
+    apply_cors, compress_response, create_error_response, create_preflight_response,
+    create_response,
+};
+use cangjie_card::storage::{push_history, save_to_redis, tolerate_redis_failure};
+use cangjie_card::utils::{ensure_cjlint_extracted, init_tracing, sanitize_repo_url};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use http::Method;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio::time::timeout;
+use tracing::{error, info, warn};
+use vercel_runtime::{run, Body, Error, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    init_tracing();
+    if let Err(e) = ensure_cjlint_extracted().await {
+        error!("Failed to extract cjlint: {}", e);
+        return Err(Error::from(e));
+    }
+
+    run(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    if req.method() == Method::OPTIONS {
+        return create_preflight_response(&req);
+    }
+
+    let response = handle(&req).await?;
+    let response = compress_response(&req, response)?;
+    Ok(apply_cors(&req, response))
+}
+
+async fn handle(req: &Request) -> Result<Response<Body>, Error> {
+    if req.method() != Method::POST {
+        return create_error_response(&RefreshError::InvalidParameter(
+            "batch expects a POST request with a JSON array of repository URLs".to_string(),
+        ));
+    }
+
+    let repos = match parse_repos(req.body()) {
+        Ok(repos) => repos,
+        Err(e) => return create_error_response(&e),
+    };
+
+    sweep_stale_repos().await;
+
+    let results = analyze_batch(repos, client_ip(req)).await;
+    create_response(StatusCode::OK, true, None, Some(results), None)
+}
+
+/// 解析请求体中的仓库列表，去除重复的地址并限制数量
+fn parse_repos(body: &[u8]) -> Result<Vec<String>, RefreshError> {
+    let repos: Vec<String> = serde_json::from_slice(body)
+        .map_err(|e| RefreshError::InvalidParameter(format!("Invalid request body: {}", e)))?;
+
+    let mut unique: Vec<String> = Vec::with_capacity(repos.len());
+    for repo in repos {
+        let repo = normalize_repo_input(&repo, None);
+        if !unique.contains(&repo) {
+            unique.push(repo);
+        }
+    }
+
+    if unique.is_empty() {
+        return Err(RefreshError::MissingRepoParam);
+    }
+    if unique.len() > batch_max_repos() {
+        return Err(RefreshError::InvalidParameter(format!(
+            "batch accepts at most {} repositories, got {}",
+            batch_max_repos(),
+            unique.len()
+        )));
+    }
+
+    Ok(unique)
+}
+
+/// 以 BATCH_CONCURRENCY 的并发度分析所有仓库，按仓库地址返回各自的结果或错误
+async fn analyze_batch(
+    repos: Vec<String>,
+    client_ip: Option<String>,
+) -> BTreeMap<String, BatchEntry> {
+    let semaphore = Arc::new(Semaphore::new(batch_concurrency()));
+    let client_ip: Option<Arc<str>> = client_ip.map(Into::into);
+
+    run_batch(repos, request_timeout_seconds(), move |repo| {
+        let semaphore = semaphore.clone();
+        let client_ip = client_ip.clone();
+        async move {
+            // 信号量不会被关闭，获取失败时直接继续
+            let _permit = semaphore.acquire_owned().await.ok();
+            analyze_repo(&repo, client_ip.as_deref()).await
+        }
+    })
+    .await
+}
+
+/// 并发执行每个仓库的分析，每个仓库都有一条结果
+///
+/// 任务崩溃时记录为内部错误；`seconds` 秒后仍未完成的任务被取消并记录为超时
+async fn run_batch<F, Fut>(
+    repos: Vec<String>,
+    seconds: u64,
+    analyze: F,
+) -> BTreeMap<String, BatchEntry>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<AnalysisResult, RefreshError>> + Send + 'static,
+{
+    let mut tasks = JoinSet::new();
+    let mut pending = HashMap::new();
+    for repo in repos {
+        let handle = tasks.spawn(analyze(repo.clone()));
+        pending.insert(handle.id(), repo);
+    }
+
+    let mut results = BTreeMap::new();
+    let collect = async {
+        while let Some(joined) = tasks.join_next_with_id().await {
+            let (id, result) = match joined {
+                Ok(joined) => joined,
+                Err(e) => {
+                    warn!("Batch task failed: {}", e);
+                    (e.id(), Err(RefreshError::Io(std::io::Error::other(e))))
+                }
+            };
+            if let Some(repo) = pending.remove(&id) {
+                let entry = batch_entry(&repo, result);
+                results.insert(repo, entry);
+            }
+        }
+    };
+    if timeout(Duration::from_secs(seconds), collect).await.is_err() {
+        warn!(unfinished = pending.len(), "Batch timed out");
+    }
+    // 丢弃 tasks 时取消仍在执行的分析
+    for repo in pending.into_values() {
+        let entry = batch_entry(&repo, Err(RefreshError::RequestTimeout { seconds }));
+        results.insert(repo, entry);
+    }
+
+    info!(repos = results.len(), "Batch completed");
+    results
+}
+
+fn batch_entry(repo: &str, result: Result<AnalysisResult, RefreshError>) -> BatchEntry {
+    match result {
+        Ok(analysis_result) => BatchEntry {
+            success: true,
+            status: StatusCode::OK.as_u16(),
+            data: Some(analysis_result),
+            error: None,
+        },
+        Err(e) => {
+            warn!(repo = %sanitize_repo_url(repo), "Batch analysis failed: {}", e);
+            BatchEntry {
+                success: false,
+                status: e.status_code().as_u16(),
+                data: None,
+                error: Some(e.to_string()),
+            }
+        }
+    }
+}
+
+/// 与 refresh 相同地分析单个仓库的默认分支，新结果写入缓存和历史
+async fn analyze_repo(repo: &str, client_ip: Option<&str>) -> Result<AnalysisResult, RefreshError> {
+    validate_repo_url(repo)?;
+    let extra_args = parse_extra_args(&cjlint_extra_args())?;
+    check_rate_limit(repo, client_ip).await?;
+
+    let mut outcome = load_or_analyze(
+        repo,
+        &CloneOptions::default(),
+        None,
+        None,
+        &extra_args,
+        AnalyzeFlags::default(),
+    )
+    .await?;
+    if !outcome.from_cache {
+        let saved = save_to_redis(repo, &serde_json::to_string(&outcome.analysis_result)?).await;
+        let saved = tolerate_redis_failure(saved, "save result")?;
+        let entry = HistoryEntry::from(&outcome.analysis_result);
+        let pushed = push_history(repo, &serde_json::to_string(&entry)?).await;
+        let pushed = tolerate_redis_failure(pushed, "push history")?;
+        if !(saved && pushed) {
+            outcome.analysis_result.cached = Some(false);
+        }
+    }
+
+    Ok(outcome.analysis_result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn every_repo_gets_an_entry() {
+        let repos = vec![
+            "https://a.example/failed".to_string(),
+            "https://b.example/panicked".to_string(),
+            "https://c.example/stuck".to_string(),
+        ];
+
+        let results = run_batch(repos, 1, |repo| async move {
+            if repo.ends_with("failed") {
+                Err(RefreshError::InvalidRepoUrl(repo))
+            } else if repo.ends_with("panicked") {
+                panic!("analysis crashed");
+            } else {
+                std::future::pending().await
+            }
+        })
+        .await;
+
+        let statuses: Vec<(&str, u16)> = results
+            .iter()
+            .map(|(repo, entry)| (repo.as_str(), entry.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("https://a.example/failed", 400),
+                ("https://b.example/panicked", 500),
+                ("https://c.example/stuck", 504),
+            ]
+        );
+        assert!(results.values().all(|entry| !entry.success && entry.error.is_some()));
+    }
+}
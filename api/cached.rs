@@ -0,0 +1,187 @@
+use cangjie_card::analysis::{paginate, sort_findings};
+use cangjie_card::error::RefreshError;
+use cangjie_card::models::{AnalysisResult, Pagination};
+use cangjie_card::response::{
+    apply_cors, compress_response, compute_etag, create_error_response,
+    create_not_modified_response, create_preflight_response, create_response, etag_matches,
+    with_etag,
+};
+use cangjie_card::storage::get_from_redis;
+use cangjie_card::utils::{init_tracing, query_params};
+use std::collections::HashMap;
+use http::Method;
+use vercel_runtime::{run, Body, Error, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    init_tracing();
+    run(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    if req.method() == Method::OPTIONS {
+        return create_preflight_response(&req);
+    }
+
+    let response = handle(&req).await?;
+    let response = compress_response(&req, response)?;
+    Ok(apply_cors(&req, response))
+}
+
+async fn handle(req: &Request) -> Result<Response<Body>, Error> {
+    let hash_query = query_params(req);
+
+    match get_cached(&hash_query).await {
+        Ok((analysis_result, etag)) => {
+            if etag_matches(req, &etag) {
+                return create_not_modified_response(&etag);
+            }
+            let response = create_response(
+                StatusCode::OK,
+                true,
+                None,
+                Some(analysis_result),
+                None,
+            )?;
+            with_etag(response, &etag)
+        }
+        Err(e) => create_error_response(&e),
+    }
+}
+
+/// 读取Redis中已保存的分析结果，指定 limit 或 offset 时只返回其中一页
+///
+/// 同时返回根据缓存内容和分页参数计算的ETag
+async fn get_cached(
+    hash_query: &HashMap<String, String>,
+) -> Result<(AnalysisResult, String), RefreshError> {
+    let repo = hash_query.get("repo").ok_or(RefreshError::MissingRepoParam)?;
+    let limit = hash_query.get("limit").map(String::as_str);
+    let offset = hash_query.get("offset").map(String::as_str);
+    let pagination = Pagination::parse(limit, offset)?;
+
+    let content = get_from_redis(repo).await?.ok_or(RefreshError::CacheMiss)?;
+    let etag = compute_etag(&[&content, limit.unwrap_or_default(), offset.unwrap_or_default()]);
+
+    let mut analysis_result: AnalysisResult = serde_json::from_str(&content)?;
+    if let Some(pagination) = pagination {
+        // 旧版本写入的缓存不保证有序，分页前按位置排序
+        sort_findings(&mut analysis_result.cjlint);
+        paginate(&mut analysis_result, pagination);
+    }
+
+    Ok((analysis_result, etag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cangjie_card::models::DefectLevel;
+    use cangjie_card::storage::save_to_redis;
+    use cangjie_card::test_support::{fake_redis, item, result, set_env};
+    use flate2::read::GzDecoder;
+    use http::header::{
+        ACCEPT_ENCODING, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, CACHE_CONTROL,
+        CONTENT_ENCODING, ETAG, IF_NONE_MATCH, ORIGIN, VARY,
+    };
+    use http::HeaderName;
+    use std::io::Read;
+
+    fn get(repo: &str, headers: &[(HeaderName, &str)]) -> Request {
+        let uri = format!("https://example.com/api/cached?repo={}", repo);
+        let mut builder = http::Request::builder().uri(uri);
+        for (name, value) in headers {
+            builder = builder.header(name, *value);
+        }
+        builder.body(Body::Empty).unwrap()
+    }
+
+    async fn cache(repo: &str) -> AnalysisResult {
+        fake_redis().await;
+        let analysis_result =
+            result(vec![item("src/main.cj", 3, "G.FMT.01", DefectLevel::Suggestions)]);
+        save_to_redis(repo, &serde_json::to_string(&analysis_result).unwrap())
+            .await
+            .unwrap();
+        analysis_result
+    }
+
+    #[tokio::test]
+    async fn unchanged_result_is_not_modified() {
+        let repo = "https://github.com/demo/cached_etag";
+        cache(repo).await;
+
+        let response = handler(get(repo, &[])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CACHE_CONTROL], "no-cache");
+        let etag = response.headers()[ETAG].to_str().unwrap().to_string();
+
+        let revalidate = format!("\"other\", W/{}", etag);
+        let response = handler(get(repo, &[(IF_NONE_MATCH, revalidate.as_str())]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[ETAG], etag.as_str());
+        assert!(matches!(response.body(), Body::Empty));
+
+        let response = handler(get(repo, &[(IF_NONE_MATCH, "\"other\"")])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // 分页参数不同的响应使用不同的ETag
+        let page = format!("{}&limit=1", repo);
+        let response = handler(get(&page, &[(IF_NONE_MATCH, etag.as_str())])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ne!(response.headers()[ETAG], etag.as_str());
+    }
+
+    #[tokio::test]
+    async fn response_is_gzipped_when_accepted() {
+        let repo = "https://github.com/demo/cached_gzip";
+        let analysis_result = cache(repo).await;
+
+        let response = handler(get(repo, &[(ACCEPT_ENCODING, "br, gzip;q=0.5")]))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[CONTENT_ENCODING], "gzip");
+        assert_eq!(response.headers()[VARY], "Accept-Encoding");
+        let Body::Binary(compressed) = response.body() else {
+            panic!("gzipped body should be binary");
+        };
+        let mut json = String::new();
+        GzDecoder::new(compressed.as_slice())
+            .read_to_string(&mut json)
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(body["data"]["commit"], analysis_result.commit.as_str());
+
+        for accept_encoding in ["gzip;q=0", "br"] {
+            let response = handler(get(repo, &[(ACCEPT_ENCODING, accept_encoding)]))
+                .await
+                .unwrap();
+            assert!(response.headers().get(CONTENT_ENCODING).is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn preflight_allows_configured_origins() {
+        let _env = set_env(&[("CORS_ALLOWED_ORIGINS", Some("https://app.example/"))]).await;
+        let preflight = |origin: &str| {
+            http::Request::builder()
+                .method(Method::OPTIONS)
+                .uri("/api/cached")
+                .header(ORIGIN, origin)
+                .body(Body::Empty)
+                .unwrap()
+        };
+
+        let response = handler(preflight("https://app.example")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], "https://app.example");
+        assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_METHODS], "GET, POST, OPTIONS");
+        assert_eq!(response.headers()[VARY], "Origin");
+
+        let response = handler(preflight("https://evil.example")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(response.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+    }
+}
@@ -0,0 +1,41 @@
+use cangjie_card::auth::authorize_admin;
+use cangjie_card::error::RefreshError;
+use cangjie_card::repository::remove_retained_repo;
+use cangjie_card::response::{create_error_response, create_response};
+use cangjie_card::utils::{init_tracing, query_params};
+use std::collections::HashMap;
+use vercel_runtime::{run, Body, Error, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    init_tracing();
+    run(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    let hash_query = query_params(&req);
+
+    match cleanup_repo(&req, &hash_query).await {
+        Ok(()) => create_response::<()>(
+            StatusCode::OK,
+            true,
+            Some("Retained repository removed"),
+            None,
+            None,
+        ),
+        Err(e) => create_error_response(&e),
+    }
+}
+
+/// 校验管理密钥后删除 keep_repo 保留的克隆目录
+async fn cleanup_repo(
+    req: &Request,
+    hash_query: &HashMap<String, String>,
+) -> Result<(), RefreshError> {
+    authorize_admin(req)?;
+
+    let path = hash_query
+        .get("path")
+        .ok_or_else(|| RefreshError::InvalidParameter("path query parameter is required".into()))?;
+    remove_retained_repo(path).await
+}
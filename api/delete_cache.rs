@@ -0,0 +1,44 @@
+use cangjie_card::auth::authorize_admin;
+use cangjie_card::error::RefreshError;
+use cangjie_card::response::{create_error_response, create_response};
+use cangjie_card::storage::delete_from_redis;
+use cangjie_card::utils::{init_tracing, query_params};
+use serde_json::json;
+use std::collections::HashMap;
+use tracing::info;
+use vercel_runtime::{run, Body, Error, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    init_tracing();
+    run(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    let hash_query = query_params(&req);
+
+    match delete_cache(&req, &hash_query).await {
+        Ok(removed) => create_response(
+            StatusCode::OK,
+            true,
+            Some("Cache deleted"),
+            Some(json!({ "removed": removed })),
+            None,
+        ),
+        Err(e) => create_error_response(&e),
+    }
+}
+
+/// 校验管理密钥后删除仓库的缓存结果
+async fn delete_cache(
+    req: &Request,
+    hash_query: &HashMap<String, String>,
+) -> Result<usize, RefreshError> {
+    authorize_admin(req)?;
+
+    let repo = hash_query.get("repo").ok_or(RefreshError::MissingRepoParam)?;
+    let removed = delete_from_redis(repo).await?;
+    info!(removed, "Cache deleted");
+
+    Ok(removed)
+}
@@ -0,0 +1,106 @@
+use cangjie_card::models::HealthStatus;
+use cangjie_card::response::{
+    apply_cors, compress_response, create_preflight_response, create_response,
+};
+use cangjie_card::storage::ping_redis;
+use cangjie_card::utils::{ensure_cjlint_extracted, init_tracing};
+use http::Method;
+use tracing::warn;
+use vercel_runtime::{run, Body, Error, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    init_tracing();
+    run(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    if req.method() == Method::OPTIONS {
+        return create_preflight_response(&req);
+    }
+
+    let response = handle().await?;
+    let response = compress_response(&req, response)?;
+    Ok(apply_cors(&req, response))
+}
+
+async fn handle() -> Result<Response<Body>, Error> {
+    // 解压失败或文件不可执行时视为工具链未就绪
+    let cjlint_ready = match ensure_cjlint_extracted().await {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("cjlint is not ready: {}", e);
+            false
+        }
+    };
+
+    let redis_ok = match ping_redis().await {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("Redis is not available: {}", e);
+            false
+        }
+    };
+
+    let status = HealthStatus {
+        cjlint_ready,
+        redis_ok,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    };
+
+    if cjlint_ready {
+        create_response(StatusCode::OK, true, None, Some(status), None)
+    } else {
+        create_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            false,
+            None,
+            Some(status),
+            Some("cjlint is not ready"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cangjie_card::config::cjlint_bin;
+    use cangjie_card::test_support::{fake_redis, set_env};
+    use std::os::unix::fs::PermissionsExt;
+
+    async fn health() -> (StatusCode, serde_json::Value) {
+        let req = http::Request::builder()
+            .uri("/api/health")
+            .body(Body::Empty)
+            .unwrap();
+        let response = handler(req).await.unwrap();
+        let body = match response.body() {
+            Body::Text(json) => serde_json::from_str(json).unwrap(),
+            _ => panic!("health should return JSON"),
+        };
+        (response.status(), body)
+    }
+
+    #[tokio::test]
+    async fn status_follows_cjlint_readiness() {
+        let home = tempfile::tempdir().unwrap();
+        let home_path = home.path().to_string_lossy().to_string();
+        fake_redis().await;
+        let _env = set_env(&[("CANGJIE_HOME", Some(home_path.as_str()))]).await;
+
+        let (status, body) = health().await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["success"], true);
+        assert_eq!(body["data"]["cjlint_ready"], true);
+        assert_eq!(body["data"]["redis_ok"], true);
+        assert_eq!(body["data"]["version"], env!("CARGO_PKG_VERSION"));
+
+        // cjlint 不可执行时工具链未就绪
+        std::fs::set_permissions(cjlint_bin(), std::fs::Permissions::from_mode(0o644)).unwrap();
+        let (status, body) = health().await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["success"], false);
+        assert_eq!(body["data"]["cjlint_ready"], false);
+        assert_eq!(body["error"], "cjlint is not ready");
+    }
+}
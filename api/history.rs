@@ -0,0 +1,51 @@
+use cangjie_card::error::RefreshError;
+use cangjie_card::models::HistoryEntry;
+use cangjie_card::response::{
+    apply_cors, compress_response, create_error_response, create_preflight_response,
+    create_response,
+};
+use cangjie_card::storage::get_history;
+use cangjie_card::utils::{init_tracing, query_params};
+use std::collections::HashMap;
+use http::Method;
+use vercel_runtime::{run, Body, Error, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    init_tracing();
+    run(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    if req.method() == Method::OPTIONS {
+        return create_preflight_response(&req);
+    }
+
+    let response = handle(&req).await?;
+    let response = compress_response(&req, response)?;
+    Ok(apply_cors(&req, response))
+}
+
+async fn handle(req: &Request) -> Result<Response<Body>, Error> {
+    let hash_query = query_params(req);
+
+    match get_repo_history(&hash_query).await {
+        Ok(history) => create_response(StatusCode::OK, true, None, Some(history), None),
+        Err(e) => create_error_response(&e),
+    }
+}
+
+/// 读取仓库的分析历史，最新的记录在前，跳过无法解析的记录
+async fn get_repo_history(
+    hash_query: &HashMap<String, String>,
+) -> Result<Vec<HistoryEntry>, RefreshError> {
+    let repo = hash_query.get("repo").ok_or(RefreshError::MissingRepoParam)?;
+
+    let history = get_history(repo)
+        .await?
+        .iter()
+        .filter_map(|entry| serde_json::from_str(entry).ok())
+        .collect();
+
+    Ok(history)
+}
@@ -0,0 +1,57 @@
+use cangjie_card::error::RefreshError;
+use cangjie_card::metrics::MetricsCollector;
+use cangjie_card::models::{AnalysisResult, CacheMetrics};
+use cangjie_card::response::{
+    apply_cors, compress_response, create_error_response, create_preflight_response,
+    create_response,
+};
+use cangjie_card::storage::scan_cached_results;
+use cangjie_card::utils::init_tracing;
+use http::Method;
+use tracing::warn;
+use vercel_runtime::{run, Body, Error, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    init_tracing();
+    run(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    if req.method() == Method::OPTIONS {
+        return create_preflight_response(&req);
+    }
+
+    let response = handle().await?;
+    let response = compress_response(&req, response)?;
+    Ok(apply_cors(&req, response))
+}
+
+async fn handle() -> Result<Response<Body>, Error> {
+    match collect_metrics().await {
+        Ok(metrics) => create_response(StatusCode::OK, true, None, Some(metrics), None),
+        Err(e) => create_error_response(&e),
+    }
+}
+
+/// 分批扫描所有仓库的最新分析结果并汇总，跳过无法解析的结果
+async fn collect_metrics() -> Result<CacheMetrics, RefreshError> {
+    let mut collector = MetricsCollector::new();
+    let mut cursor = 0;
+    loop {
+        let (next_cursor, contents) = scan_cached_results(cursor).await?;
+        for content in contents {
+            match serde_json::from_str::<AnalysisResult>(&content) {
+                Ok(analysis_result) => collector.add(&analysis_result),
+                Err(e) => warn!("Skipping unparseable cached result: {}", e),
+            }
+        }
+
+        if next_cursor == 0 {
+            break;
+        }
+        cursor = next_cursor;
+    }
+
+    Ok(collector.finish())
+}
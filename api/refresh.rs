@@ -1,155 +1,1164 @@
-use cangjie_card::analysis::{process_analysis_result, run_cjlint};
-use cangjie_card::models::{AnalysisResult, AnalysisResultItem, ApiResponse};
-use cangjie_card::repository::{clone_repository, find_package_name, RepoCleanup};
-use cangjie_card::storage::save_to_redis;
-use cangjie_card::utils::ensure_cjlint_extracted;
-use serde::Serialize;
+use cangjie_card::analysis::{
+    apply_severity_overrides, diff_against_base, effective_max_findings, filter_by_language,
+    filter_by_level, filter_by_paths, filter_to_changed, lint_roots, paginate, parse_extra_args,
+    parse_list, parse_path_patterns, parse_severity_overrides, run_cjlint_raw, sort_by_order,
+    suppress, truncate_findings,
+};
+use cangjie_card::auth::authorize_admin;
+use cangjie_card::baseline::apply_baseline;
+use cangjie_card::callback::{notify_callback, validate_callback_url, CallbackPayload};
+use cangjie_card::config::{
+    allow_keep_repo, background_jobs, cjlint_extra_args, request_timeout_seconds,
+    severity_overrides,
+};
+use cangjie_card::error::RefreshError;
+use cangjie_card::jobs::{Job, JobState};
+use cangjie_card::models::{
+    CloneOptions, CloneTarget, DefectLevel, DryRunResult, HistoryEntry, LevelFilter, Pagination,
+    RefreshRequest, SortOrder,
+};
+use cangjie_card::pipeline::{
+    analyze_local, load_or_analyze, with_request_timeout, AnalyzeFlags, RefreshOutcome,
+};
+use cangjie_card::rate_limit::{check_rate_limit, client_ip};
+use cangjie_card::report::codeclimate::to_codeclimate;
+use cangjie_card::report::csv::{csv_filename, to_csv};
+use cangjie_card::report::fields::{parse_fields, project_fields};
+use cangjie_card::report::github::to_github_annotations;
+use cangjie_card::report::gitlab::to_gitlab;
+use cangjie_card::report::grouped::group_by_file;
+use cangjie_card::report::junit::to_junit;
+use cangjie_card::report::markdown::to_markdown;
+use cangjie_card::report::nested::{to_nested, ItemShape};
+use cangjie_card::report::sarif::to_sarif;
+use cangjie_card::report::OutputFormat;
+use cangjie_card::repository::{
+    check_retention_capacity, clone_repository, count_cangjie_files, find_packages,
+    normalize_repo_input, repo_name, resolve_subdir, sweep_stale_repos, validate_local_path,
+    validate_repo_url, validate_subdir,
+};
+use cangjie_card::response::{
+    apply_cors, compress_response, create_error_response, create_preflight_response,
+    create_raw_response, create_response, with_server_timing,
+};
+use cangjie_card::rules::RuleConfig;
+use cangjie_card::storage::{get_baseline, push_history, save_to_redis, tolerate_redis_failure};
+use cangjie_card::utils::{ensure_cjlint_extracted, init_tracing, query_params, sanitize_repo_url};
 use std::collections::HashMap;
-use std::time::SystemTime;
-use url::Url;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use http::header::CONTENT_DISPOSITION;
+use http::{HeaderValue, Method};
+use tokio::time::timeout;
+use tracing::{error, info, instrument, warn, Span};
 use vercel_runtime::{run, Body, Error, Request, Response, StatusCode};
 
-fn create_response<T: Serialize>(
-    status_code: StatusCode,
-    success: bool,
-    message: Option<&str>,
-    data: Option<T>,
-    error: Option<&str>,
-) -> Result<Response<Body>, Error> {
-    let response = ApiResponse {
-        success,
-        message: message.map(String::from),
-        data,
-        error: error.map(String::from),
-    };
-
-    let body = serde_json::to_string(&response)
-        .map_err(|e| Error::from(format!("Failed to serialize response: {}", e)))?;
-
-    Ok(Response::builder()
-        .status(status_code)
-        .header("Content-Type", "application/json")
-        .body(Body::from(body))?)
-}
-
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    eprintln!("Starting...");
+    init_tracing();
+    info!("Starting...");
     if let Err(e) = ensure_cjlint_extracted().await {
-        eprintln!("Failed to extract cjlint: {}", e);
+        error!("Failed to extract cjlint: {}", e);
         return Err(Error::from(e));
     }
-    eprintln!("cjlint extracted");
+    info!("cjlint extracted");
 
     run(handler).await
 }
 
 pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
-    let url = Url::parse(&req.uri().to_string()).unwrap();
-    let hash_query: HashMap<String, String> = url.query_pairs().into_owned().collect();
-    let repo = hash_query.get("repo");
-    let repo = match repo {
-        Some(repo) => repo,
-        None => {
-            return create_response::<()>(
-                StatusCode::BAD_REQUEST,
-                false,
-                None,
-                None,
-                Some("repo query parameter is required"),
-            );
+    if req.method() == Method::OPTIONS {
+        return create_preflight_response(&req);
+    }
+
+    // 超时时丢弃处理中的 future，cjlint子进程随之结束；阻塞线程上的克隆收到取消标记后中止，
+    // 克隆目录和克隆名额在该线程退出时释放
+    let seconds = request_timeout_seconds();
+    let response = match timeout(Duration::from_secs(seconds), handle(&req)).await {
+        Ok(response) => response?,
+        Err(_) => {
+            error!(seconds, "Request timed out");
+            create_error_response(&RefreshError::RequestTimeout { seconds })?
         }
     };
+    let response = compress_response(&req, response)?;
+    Ok(apply_cors(&req, response))
+}
 
-    let clone_result = match clone_repository(repo).await {
-        Ok(result) => result,
-        Err(e) => {
-            return create_response::<()>(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                false,
-                None,
-                None,
-                Some(&format!("Failed to clone repository: {}", e)),
-            );
+async fn handle(req: &Request) -> Result<Response<Body>, Error> {
+    sweep_stale_repos().await;
+
+    let hash_query = query_params(req);
+
+    // POST请求从JSON请求体读取参数，GET请求保持使用查询参数
+    let mut request = if req.method() == Method::POST {
+        match RefreshRequest::from_json(req.body()) {
+            Ok(request) => request,
+            Err(e) => return create_error_response(&e),
+        }
+    } else {
+        match RefreshRequest::from_query(&hash_query) {
+            Ok(request) => request,
+            Err(e) => return create_error_response(&e),
         }
     };
 
-    let mut repo_cleanup = RepoCleanup::new(clone_result.repo_path.clone());
+    let token = auth_token(req, &hash_query);
 
-    let package_name = match find_package_name(clone_result.repo_path.clone()).await {
-        Ok(name) => name,
-        Err(e) => {
-            return create_response::<()>(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                false,
-                None,
-                None,
-                Some(&format!("Failed to find package name: {}", e)),
-            );
+    // 在做任何工作之前拒绝空白或明显不是URL的仓库地址，避免错误在克隆时才出现
+    request.repo = request
+        .repo
+        .map(|repo| normalize_repo_input(&repo, token.as_deref()))
+        .filter(|repo| !repo.is_empty());
+    let repo_check = match (request.repo.as_deref(), &request.local_path) {
+        (Some(repo), _) => validate_repo_url(repo).map(|_| ()),
+        // 分析本地目录时不需要仓库地址
+        (None, Some(_)) => Ok(()),
+        (None, None) => Err(RefreshError::MissingRepoParam),
+    };
+    if let Err(e) = repo_check {
+        return create_error_response(&e);
+    }
+    if request.changed_only.unwrap_or(false) && request.base.is_none() {
+        return create_error_response(&RefreshError::InvalidParameter(
+            "changed_only requires base".to_string(),
+        ));
+    }
+
+    // 本地目录位于服务器上，只允许管理员分析
+    if request.local_path.is_some() {
+        if let Err(e) = authorize_admin(req) {
+            return create_error_response(&e);
+        }
+    }
+
+    // 保留克隆目录会占用磁盘，需要服务端开启并提供管理密钥
+    if request.keep_repo.unwrap_or(false) {
+        if !allow_keep_repo() {
+            return create_error_response(&RefreshError::InvalidParameter(
+                "keep_repo is not enabled on this server".to_string(),
+            ));
         }
+        if let Err(e) = authorize_admin(req) {
+            return create_error_response(&e);
+        }
+        // 在克隆之前检查数量上限，避免完成分析后才拒绝
+        if let Err(e) = check_retention_capacity() {
+            return create_error_response(&e);
+        }
+    }
+
+    let format: OutputFormat = match request.format.as_ref().map(|f| f.parse()) {
+        Some(Ok(format)) => format,
+        Some(Err(e)) => return create_error_response(&e),
+        None => OutputFormat::Json,
     };
 
-    // 使用 cjlint 检查代码
-    let content = match run_cjlint(clone_result.repo_path.clone()).await {
-        Ok(result) => result,
-        Err(e) => {
-            return create_response::<()>(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                false,
-                None,
-                None,
-                Some(&format!("Failed to run cjlint: {}", e)),
-            );
+    let group_by_file_enabled = match request.group_by.as_deref() {
+        None => false,
+        Some("file") => true,
+        Some(other) => {
+            return create_error_response(&RefreshError::InvalidParameter(format!(
+                "Unsupported group_by: {}",
+                other
+            )));
         }
     };
 
-    let analysis_result: Vec<AnalysisResultItem> = match serde_json::from_str(&content) {
-        Ok(result) => result,
-        Err(e) => {
-            return create_response::<()>(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                false,
+    // JUnit 输出中建议问题默认记为 skipped，可以要求记为 failure
+    let suggestions_as_failures = match request.junit_suggestions.as_deref() {
+        None | Some("skipped") => false,
+        Some("failure") => true,
+        Some(other) => {
+            return create_error_response(&RefreshError::InvalidParameter(format!(
+                "Unsupported junit_suggestions: {}",
+                other
+            )));
+        }
+    };
+
+    // 嵌套位置只用于未分组的JSON输出
+    let item_shape: ItemShape = match request.shape.as_deref().map(str::parse) {
+        Some(Ok(ItemShape::Nested)) if group_by_file_enabled => {
+            return create_error_response(&RefreshError::InvalidParameter(
+                "shape=nested cannot be combined with group_by".to_string(),
+            ));
+        }
+        Some(Ok(shape)) => shape,
+        Some(Err(e)) => return create_error_response(&e),
+        None => ItemShape::Flat,
+    };
+
+    // 字段投影只用于未分组、扁平位置的JSON输出
+    let fields = match request.fields.as_deref().map(parse_fields) {
+        Some(Ok(_)) if format != OutputFormat::Json => {
+            return create_error_response(&RefreshError::InvalidParameter(
+                "fields is only supported with JSON output".to_string(),
+            ));
+        }
+        Some(Ok(_)) if group_by_file_enabled || item_shape == ItemShape::Nested => {
+            return create_error_response(&RefreshError::InvalidParameter(
+                "fields cannot be combined with group_by or shape=nested".to_string(),
+            ));
+        }
+        Some(Ok(fields)) => Some(fields),
+        Some(Err(e)) => return create_error_response(&e),
+        None => None,
+    };
+
+    let sort_order: SortOrder = match request.sort.as_deref().map(str::parse) {
+        Some(Ok(order)) => order,
+        Some(Err(e)) => return create_error_response(&e),
+        None => SortOrder::Position,
+    };
+
+    let pagination = match Pagination::parse(request.limit.as_deref(), request.offset.as_deref()) {
+        Ok(pagination) => pagination,
+        Err(e) => return create_error_response(&e),
+    };
+    let max_findings = match effective_max_findings(request.max_findings.as_deref()) {
+        Ok(max_findings) => max_findings,
+        Err(e) => return create_error_response(&e),
+    };
+
+    let started_at = Instant::now();
+    let client_ip = client_ip(req);
+
+    if request.dry_run.unwrap_or(false) {
+        return match dry_run(&request, token, client_ip.as_deref()).await {
+            Ok(result) => create_response(
+                StatusCode::OK,
+                true,
+                Some("Dry run completed, cjlint was not run"),
+                Some(result),
                 None,
+            ),
+            Err(e) => create_error_response(&e),
+        };
+    }
+
+    // 原始输出包含服务器上的绝对路径，只提供给管理员排查问题
+    if request.raw.unwrap_or(false) {
+        if let Err(e) = authorize_admin(req) {
+            return create_error_response(&e);
+        }
+        if request.local_path.is_some() {
+            return create_error_response(&RefreshError::InvalidParameter(
+                "raw is not supported with local_path".to_string(),
+            ));
+        }
+        return match raw_output(&request, token, client_ip.as_deref()).await {
+            Ok(content) => create_raw_response(StatusCode::OK, "application/json", content),
+            Err(e) => create_error_response(&e),
+        };
+    }
+
+    if request.async_mode.unwrap_or(false) {
+        if request.local_path.is_some() {
+            return create_error_response(&RefreshError::InvalidParameter(
+                "async is not supported with local_path".to_string(),
+            ));
+        }
+        return match enqueue(request, token, client_ip).await {
+            Ok(job) => create_response(
+                StatusCode::ACCEPTED,
+                true,
+                Some("Refresh queued, poll the status endpoint for the result"),
+                Some(job),
                 None,
-                Some(&format!("Failed to parse cjlint output: {}", e)),
+            ),
+            Err(e) => create_error_response(&e),
+        };
+    }
+
+    let result = match &request.local_path {
+        Some(local_path) => refresh_local(&request, local_path).await,
+        None => refresh(&request, token, client_ip.as_deref()).await,
+    };
+    if let Ok(outcome) = &result {
+        info!(
+            findings = outcome.analysis_result.cjlint.len(),
+            duration_ms = started_at.elapsed().as_millis() as u64,
+            "Refresh completed"
+        );
+    }
+
+    match result {
+        Ok(RefreshOutcome {
+            mut analysis_result,
+            message,
+            from_cache,
+            retained_path,
+            ..
+        }) => {
+            let timings = analysis_result.timings;
+            analysis_result.retained_path =
+                retained_path.map(|path| path.to_string_lossy().to_string());
+            // 缓存中的结果已按位置排序
+            if sort_order != SortOrder::Position {
+                sort_by_order(&mut analysis_result.cjlint, sort_order);
+            }
+            if let Some(pagination) = pagination {
+                paginate(&mut analysis_result, pagination);
+            }
+            truncate_findings(&mut analysis_result, max_findings);
+
+            // cjlint非零退出时仍返回已产出的结果，但以422提示结果可能不完整
+            let (status, success, error) = match analysis_result.cjlint_exit_code {
+                Some(code) => (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    false,
+                    Some(format!(
+                        "cjlint exited with code {}; partial results returned",
+                        code
+                    )),
+                ),
+                None => (StatusCode::OK, true, None),
+            };
+
+            let response = match format {
+                OutputFormat::Json if group_by_file_enabled => create_response(
+                    status,
+                    success,
+                    Some(message),
+                    Some(group_by_file(&analysis_result.cjlint)?),
+                    error.as_deref(),
+                ),
+                OutputFormat::Json if item_shape == ItemShape::Nested => {
+                    let items = std::mem::take(&mut analysis_result.cjlint);
+                    create_response(
+                        status,
+                        success,
+                        Some(message),
+                        Some(to_nested(&analysis_result, &items)?),
+                        error.as_deref(),
+                    )
+                }
+                OutputFormat::Json if fields.is_some() => create_response(
+                    status,
+                    success,
+                    Some(message),
+                    Some(project_fields(&analysis_result, fields.as_deref().unwrap_or_default())?),
+                    error.as_deref(),
+                ),
+                OutputFormat::Json => create_response(
+                    status,
+                    success,
+                    Some(message),
+                    Some(analysis_result),
+                    error.as_deref(),
+                ),
+                OutputFormat::Sarif => create_raw_response(
+                    status,
+                    format.content_type(),
+                    serde_json::to_string(&to_sarif(&analysis_result.cjlint))?,
+                ),
+                OutputFormat::CodeClimate => create_raw_response(
+                    status,
+                    format.content_type(),
+                    serde_json::to_string(&to_codeclimate(&analysis_result.cjlint))?,
+                ),
+                OutputFormat::Gitlab => create_raw_response(
+                    status,
+                    format.content_type(),
+                    serde_json::to_string(&to_gitlab(&analysis_result.cjlint))?,
+                ),
+                OutputFormat::GithubAnnotations => create_raw_response(
+                    status,
+                    format.content_type(),
+                    to_github_annotations(&analysis_result.cjlint),
+                ),
+                OutputFormat::Csv => {
+                    let mut response = create_raw_response(
+                        status,
+                        format.content_type(),
+                        to_csv(&analysis_result.cjlint),
+                    )?;
+                    let disposition = format!(
+                        "attachment; filename=\"{}\"",
+                        csv_filename(&analysis_result.package_name)
+                    );
+                    response
+                        .headers_mut()
+                        .insert(CONTENT_DISPOSITION, HeaderValue::from_str(&disposition)?);
+                    Ok(response)
+                }
+                OutputFormat::Junit => create_raw_response(
+                    status,
+                    format.content_type(),
+                    to_junit(&analysis_result.cjlint, suggestions_as_failures),
+                ),
+                OutputFormat::Markdown => create_raw_response(
+                    status,
+                    format.content_type(),
+                    to_markdown(&analysis_result, request.repo.as_deref()),
+                ),
+            }?;
+
+            // 缓存结果的耗时属于之前的请求，不再报告
+            if from_cache {
+                Ok(response)
+            } else {
+                Ok(with_server_timing(response, &timings))
+            }
+        }
+        Err(e) => {
+            error!(
+                duration_ms = started_at.elapsed().as_millis() as u64,
+                "Refresh failed: {}", e
             );
+            create_error_response(&e)
         }
-    };
+    }
+}
 
-    // 处理file字段，去除repo_path前缀
-    let repo_path = clone_result.repo_path.clone();
-    let processed_analysis_result = process_analysis_result(analysis_result, &repo_path);
+/// 保存 pending 状态的任务并执行刷新，返回任务的状态
+///
+/// 开启 BACKGROUND_JOBS 时在后台执行并立即返回初始状态，后台任务单独按 REQUEST_TIMEOUT_SECONDS
+/// 限制执行时间；否则在返回前执行完任务并返回最终状态
+async fn enqueue(
+    request: RefreshRequest,
+    token: Option<String>,
+    client_ip: Option<String>,
+) -> Result<serde_json::Value, RefreshError> {
+    let repo = request.repo.as_deref().ok_or(RefreshError::MissingRepoParam)?;
+    validate_repo_url(repo)?;
 
-    let analysis_result = AnalysisResult {
-        cjlint: processed_analysis_result,
-        created_at: SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64,
-        commit: clone_result.commit_hash,
-        package_name,
+    let job = Job::new(repo);
+    job.save().await?;
+    info!(job = %job.id, "Refresh queued");
+
+    if background_jobs() {
+        let accepted = serde_json::to_value(&job)?;
+        tokio::spawn(run_job(job, request, token, client_ip));
+        return Ok(accepted);
+    }
+
+    let job = run_job(job, request, token, client_ip).await;
+    Ok(serde_json::to_value(&job)?)
+}
+
+/// 执行刷新任务并把最终状态写回Redis
+async fn run_job(
+    mut job: Job,
+    request: RefreshRequest,
+    token: Option<String>,
+    client_ip: Option<String>,
+) -> Job {
+    job.advance(JobState::Running);
+    if let Err(e) = job.save().await {
+        warn!(job = %job.id, "Failed to save job status: {}", e);
+    }
+
+    let result = with_request_timeout(refresh(&request, token, client_ip.as_deref())).await;
+    match result {
+        Ok(outcome) => job.complete(outcome.analysis_result),
+        Err(e) => {
+            error!(job = %job.id, "Background refresh failed: {}", e);
+            job.fail(&e);
+        }
+    }
+
+    if let Err(e) = job.save().await {
+        error!(job = %job.id, "Failed to save job status: {}", e);
+    }
+    job
+}
+
+/// 从Authorization请求头或token参数中读取访问私有仓库的令牌
+fn auth_token(req: &Request, hash_query: &HashMap<String, String>) -> Option<String> {
+    let header_token = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| {
+            value
+                .strip_prefix("Bearer ")
+                .or_else(|| value.strip_prefix("token "))
+                .unwrap_or(value)
+                .trim()
+                .to_string()
+        });
+
+    header_token
+        .or_else(|| hash_query.get("token").cloned())
+        .filter(|token| !token.is_empty())
+}
+
+/// 解析请求参数并返回分析结果，远端提交已有缓存时跳过克隆
+#[instrument(skip_all, fields(repo, commit))]
+async fn refresh(
+    request: &RefreshRequest,
+    token: Option<String>,
+    client_ip: Option<&str>,
+) -> Result<RefreshOutcome, RefreshError> {
+    let repo = request.repo.as_deref().ok_or(RefreshError::MissingRepoParam)?;
+    validate_repo_url(repo)?;
+    Span::current().record("repo", sanitize_repo_url(repo).as_str());
+
+    let level: LevelFilter = match &request.level {
+        Some(level) => level.parse()?,
+        None => LevelFilter::All,
     };
+    let extra_args = extra_args(request)?;
+    let clone_options = clone_options(request, token)?;
+    let subdir = request.path.as_deref().map(validate_subdir).transpose()?;
+    let rule_config = RuleConfig::from_request(
+        request.rule_config.as_ref(),
+        request.rule_config_path.as_deref(),
+    )?;
+    let include_snippets = request.include_snippets.unwrap_or(false);
+    // 只检查变更文件时用基准版本筛选问题，不再完整分析基准版本
+    let changed_since = request.base.as_deref().filter(|_| request.changed_only.unwrap_or(false));
+    let overrides = overrides(request)?;
+    let callback_url = request
+        .callback_url
+        .as_deref()
+        .map(validate_callback_url)
+        .transpose()?;
 
-    // 将结果保存到Redis
-    if let Err(e) = save_to_redis(repo, &serde_json::to_string(&analysis_result).unwrap()).await {
-        return create_response::<()>(
-            StatusCode::INTERNAL_SERVER_ERROR,
-            false,
-            None,
-            None,
-            Some(&format!("Failed to save to Redis: {}", e)),
+    // 参数校验通过后再计数，避免无效请求占用额度。
+    // 强制刷新与普通请求一样计数，因此无法绕过限流反复触发克隆
+    check_rate_limit(repo, client_ip).await?;
+
+    let mut outcome = load_or_analyze(
+        repo,
+        &clone_options,
+        subdir.as_deref(),
+        rule_config.as_ref(),
+        &extra_args,
+        AnalyzeFlags {
+            include_snippets,
+            keep_repo: request.keep_repo.unwrap_or(false),
+            force: request.force.unwrap_or(false),
+            changed_since,
+        },
+    )
+    .await?;
+    Span::current().record("commit", outcome.analysis_result.commit.as_str());
+    // 只有默认分支的结果才会作为仓库的最新结果，子目录、自定义规则和附带片段的结果只按提交缓存；
+    // 最新结果无需认证即可读取，使用令牌克隆的结果同样不写入
+    if !outcome.from_cache
+        && is_default_run(
+            &clone_options,
+            subdir.as_deref(),
+            rule_config.as_ref(),
+            &extra_args,
+            include_snippets,
+        )
+    {
+        let saved = save_to_redis(repo, &serde_json::to_string(&outcome.analysis_result)?).await;
+        let saved = tolerate_redis_failure(saved, "save result")?;
+        let entry = HistoryEntry::from(&outcome.analysis_result);
+        let pushed = push_history(repo, &serde_json::to_string(&entry)?).await;
+        let pushed = tolerate_redis_failure(pushed, "push history")?;
+        if !(saved && pushed) {
+            outcome.analysis_result.cached = Some(false);
+        }
+    }
+    // 缓存中保留cjlint报告的原始级别
+    apply_severity_overrides(&mut outcome.analysis_result, &overrides);
+    if let Some(callback_url) = &callback_url {
+        notify_callback(callback_url, &CallbackPayload::new(repo, &outcome.analysis_result)).await;
+    }
+
+    // 基准版本只按提交缓存，不覆盖仓库的最新结果
+    if let Some(base) = request.base.as_ref().filter(|_| changed_since.is_none()) {
+        let base_options = CloneOptions {
+            target: CloneTarget::from_revision(base),
+            token: clone_options.token.clone(),
+            submodules: clone_options.submodules,
+            depth: clone_options.depth,
+        };
+        let base_outcome = load_or_analyze(
+            repo,
+            &base_options,
+            subdir.as_deref(),
+            rule_config.as_ref(),
+            &extra_args,
+            AnalyzeFlags::default(),
+        )
+        .await?;
+        let diff = diff_against_base(
+            &mut outcome.analysis_result.cjlint,
+            &base_outcome.analysis_result,
         );
+        outcome.analysis_result.summary.diff = Some(diff);
     }
 
-    if let Err(e) = repo_cleanup.cleanup().await {
-        eprintln!("Warning: Failed to clean up repository: {}", e);
+    filter_findings(request, &mut outcome, Some(repo), level).await?;
+
+    Ok(outcome)
+}
+
+/// 分析服务器上的本地目录，不克隆也不读写缓存和历史
+async fn refresh_local(
+    request: &RefreshRequest,
+    local_path: &str,
+) -> Result<RefreshOutcome, RefreshError> {
+    let dir = validate_local_path(local_path)?;
+    if request.base.is_some() {
+        return Err(RefreshError::InvalidParameter(
+            "base is not supported with local_path".to_string(),
+        ));
     }
 
-    return create_response(
-        StatusCode::OK,
-        true,
-        Some("Analysis completed successfully"),
-        Some(analysis_result),
-        None,
+    let level: LevelFilter = match &request.level {
+        Some(level) => level.parse()?,
+        None => LevelFilter::All,
+    };
+    let extra_args = extra_args(request)?;
+    let subdir = request.path.as_deref().map(validate_subdir).transpose()?;
+    let rule_config = RuleConfig::from_request(
+        request.rule_config.as_ref(),
+        request.rule_config_path.as_deref(),
+    )?;
+    let overrides = overrides(request)?;
+
+    let mut outcome = analyze_local(
+        &dir,
+        subdir.as_deref(),
+        rule_config.as_ref(),
+        &extra_args,
+        request.include_snippets.unwrap_or(false),
+    )
+    .await?;
+    apply_severity_overrides(&mut outcome.analysis_result, &overrides);
+    filter_findings(request, &mut outcome, request.repo.as_deref(), level).await?;
+
+    Ok(outcome)
+}
+
+/// 服务端配置的cjlint参数在前，请求中的参数在后
+fn extra_args(request: &RefreshRequest) -> Result<Vec<String>, RefreshError> {
+    let mut extra_args = parse_extra_args(&cjlint_extra_args())?;
+    if let Some(raw) = &request.extra_args {
+        extra_args.extend(parse_extra_args(raw)?);
+    }
+    Ok(extra_args)
+}
+
+/// 请求中的级别覆盖配置优先于服务端配置
+fn overrides(request: &RefreshRequest) -> Result<HashMap<String, DefectLevel>, RefreshError> {
+    let mut overrides = severity_overrides();
+    if let Some(raw) = &request.severity_overrides {
+        overrides.extend(parse_severity_overrides(raw)?);
+    }
+    Ok(overrides)
+}
+
+/// 按请求过滤返回给调用方的问题，只有提供仓库地址时才能按基线过滤
+///
+/// 缓存中保留完整结果，只过滤返回给调用方的部分，summary 仍为完整统计
+async fn filter_findings(
+    request: &RefreshRequest,
+    outcome: &mut RefreshOutcome,
+    repo: Option<&str>,
+    level: LevelFilter,
+) -> Result<(), RefreshError> {
+    if let Some(changed) = outcome.changed_files.take() {
+        outcome.analysis_result.summary.not_changed =
+            filter_to_changed(&mut outcome.analysis_result.cjlint, &changed);
+        outcome.analysis_result.changed_files = Some(changed.into_iter().collect());
+    }
+    let ignore_analyzers = request
+        .ignore_analyzers
+        .as_deref()
+        .map(parse_list)
+        .unwrap_or_default();
+    let ignore_types = request.ignore_types.as_deref().map(parse_list).unwrap_or_default();
+    outcome.analysis_result.summary.suppressed = suppress(
+        &mut outcome.analysis_result.cjlint,
+        &ignore_analyzers,
+        &ignore_types,
     );
+    if let Some(repo) = repo.filter(|_| request.suppress_baseline.unwrap_or(false)) {
+        let baseline = get_baseline(repo).await?;
+        outcome.analysis_result.summary.baseline_suppressed =
+            apply_baseline(&mut outcome.analysis_result.cjlint, &baseline);
+    }
+    let include = match &request.include_paths {
+        Some(raw) => parse_path_patterns(raw, "include_paths")?,
+        None => Vec::new(),
+    };
+    let exclude = match &request.exclude_paths {
+        Some(raw) => parse_path_patterns(raw, "exclude_paths")?,
+        None => Vec::new(),
+    };
+    let summary = &mut outcome.analysis_result.summary;
+    (summary.excluded_files, summary.excluded_findings) =
+        filter_by_paths(&mut outcome.analysis_result.cjlint, &include, &exclude);
+    filter_by_level(&mut outcome.analysis_result.cjlint, level);
+    if let Some(language) = &request.language {
+        filter_by_language(&mut outcome.analysis_result.cjlint, language);
+    }
+
+    Ok(())
+}
+
+/// 使用默认选项分析默认分支的整个仓库，结果可以代表仓库的最新状态
+fn is_default_run(
+    clone_options: &CloneOptions,
+    subdir: Option<&Path>,
+    rule_config: Option<&RuleConfig>,
+    extra_args: &[String],
+    include_snippets: bool,
+) -> bool {
+    clone_options.target == CloneTarget::Default
+        && clone_options.token.is_none()
+        && !clone_options.submodules
+        && subdir.is_none()
+        && rule_config.is_none()
+        && !include_snippets
+        // 服务端 CJLINT_EXTRA_ARGS 对所有请求都生效，不影响结果能否代表仓库
+        && parse_extra_args(&cjlint_extra_args()).is_ok_and(|defaults| defaults == extra_args)
+}
+
+/// 根据请求参数构造克隆选项
+fn clone_options(
+    request: &RefreshRequest,
+    token: Option<String>,
+) -> Result<CloneOptions, RefreshError> {
+    let target = match (&request.reference, &request.branch, &request.commit) {
+        (None, None, None) => CloneTarget::Default,
+        (Some(reference), None, None) => CloneTarget::Ref(reference.clone()),
+        (None, Some(branch), None) => CloneTarget::Branch(branch.clone()),
+        (None, None, Some(commit)) => CloneTarget::Commit(commit.clone()),
+        _ => {
+            return Err(RefreshError::InvalidParameter(
+                "Only one of ref, branch and commit can be specified".to_string(),
+            ));
+        }
+    };
+
+    // full 或 0 表示完整历史
+    let depth = match request.depth.as_deref() {
+        None => None,
+        Some("full") => Some(0),
+        Some(raw) => Some(raw.parse::<u32>().map_err(|_| {
+            RefreshError::InvalidParameter(format!(
+                "depth expects a non-negative integer or full, got {}",
+                raw
+            ))
+        })?),
+    };
+
+    Ok(CloneOptions {
+        target,
+        token,
+        submodules: request.submodules.unwrap_or(false),
+        depth,
+    })
+}
+
+/// 只克隆仓库并识别包信息，不运行cjlint，也不写入Redis
+#[instrument(skip_all, fields(repo, commit))]
+async fn dry_run(
+    request: &RefreshRequest,
+    token: Option<String>,
+    client_ip: Option<&str>,
+) -> Result<DryRunResult, RefreshError> {
+    let repo = request.repo.as_deref().ok_or(RefreshError::MissingRepoParam)?;
+    let repo_url = validate_repo_url(repo)?;
+    Span::current().record("repo", sanitize_repo_url(repo).as_str());
+
+    let clone_options = clone_options(request, token)?;
+    check_rate_limit(repo, client_ip).await?;
+
+    let clone_result = clone_repository(repo, &clone_options).await?;
+    Span::current().record("commit", clone_result.commit_hash.as_str());
+
+    let packages = find_packages(clone_result.repo_path.clone(), &repo_name(&repo_url)).await?;
+    let cj_file_count = count_cangjie_files(&clone_result.repo_path)?;
+
+    if let Err(e) = clone_result.repo_dir.close() {
+        warn!("Failed to clean up repository: {}", e);
+    }
+
+    Ok(DryRunResult {
+        commit: clone_result.commit_hash,
+        package_name: packages[0].1.clone(),
+        packages: packages.into_iter().map(|(_, name)| name).collect(),
+        cj_file_count,
+    })
+}
+
+/// 克隆仓库并返回cjlint写出的原始报告，跳过缓存和所有后续处理
+///
+/// 仓库中有多个需要单独检查的包时，各个报告按检查顺序放在JSON数组中
+#[instrument(skip_all, fields(repo, commit))]
+async fn raw_output(
+    request: &RefreshRequest,
+    token: Option<String>,
+    client_ip: Option<&str>,
+) -> Result<String, RefreshError> {
+    let repo = request.repo.as_deref().ok_or(RefreshError::MissingRepoParam)?;
+    let repo_url = validate_repo_url(repo)?;
+    Span::current().record("repo", sanitize_repo_url(repo).as_str());
+
+    let extra_args = extra_args(request)?;
+    let clone_options = clone_options(request, token)?;
+    let subdir = request.path.as_deref().map(validate_subdir).transpose()?;
+    check_rate_limit(repo, client_ip).await?;
+
+    let clone_result = clone_repository(repo, &clone_options).await?;
+    Span::current().record("commit", clone_result.commit_hash.as_str());
+
+    let lint_dirs = match subdir {
+        Some(subdir) => vec![resolve_subdir(&clone_result.repo_path, &subdir)?],
+        None => {
+            let packages =
+                find_packages(clone_result.repo_path.clone(), &repo_name(&repo_url)).await?;
+            lint_roots(&packages)
+        }
+    };
+
+    let mut reports = Vec::with_capacity(lint_dirs.len());
+    for lint_dir in lint_dirs {
+        reports.push(run_cjlint_raw(lint_dir.to_string_lossy().to_string(), &extra_args).await?);
+    }
+
+    if let Err(e) = clone_result.repo_dir.close() {
+        warn!("Failed to clean up repository: {}", e);
+    }
+
+    Ok(match <[String; 1]>::try_from(reports) {
+        Ok([report]) => report,
+        Err(reports) => format!("[{}]", reports.join(",")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cangjie_card::repository::normalize_clone_url;
+    use cangjie_card::test_support::{
+        commit_file, fake_cjlint, fake_cjlint_calls, fake_redis, fixture_package, fixture_repo,
+        hanging_server, set_env, EnvGuard,
+    };
+    use std::path::PathBuf;
+
+    fn get(query: &str) -> Request {
+        http::Request::builder()
+            .uri(format!("https://example.com/api/refresh?{}", query))
+            .body(Body::Empty)
+            .unwrap()
+    }
+
+    fn json_body(response: &Response<Body>) -> serde_json::Value {
+        match response.body() {
+            Body::Text(json) => serde_json::from_str(json).unwrap(),
+            _ => panic!("refresh should return JSON"),
+        }
+    }
+
+    /// 使用 `home` 中模拟的cjlint，允许克隆 fixture_repo 创建的仓库，克隆目录放在 `temp` 中
+    async fn fixture_env(home: &Path, temp: &Path) -> EnvGuard {
+        let home_path = home.to_string_lossy().to_string();
+        let temp_dir = temp.to_string_lossy().to_string();
+        set_env(&[
+            ("CANGJIE_HOME", Some(home_path.as_str())),
+            ("TEMP_DIR", Some(temp_dir.as_str())),
+            ("ALLOWED_REPO_HOSTS", Some("fixture.test")),
+            ("RATE_LIMIT_MAX_REQUESTS", Some("0")),
+        ])
+        .await
+    }
+
+    #[tokio::test]
+    async fn only_default_runs_update_the_latest_result() {
+        let _env = set_env(&[("CJLINT_EXTRA_ARGS", Some("-j 2"))]).await;
+        let defaults = vec!["-j".to_string(), "2".to_string()];
+        let options = CloneOptions::default();
+        let subdir = Path::new("src");
+        let rules = RuleConfig::Inline("{}".to_string());
+
+        assert!(is_default_run(&options, None, None, &defaults, false));
+        assert!(!is_default_run(&options, Some(subdir), None, &defaults, false));
+        assert!(!is_default_run(&options, None, Some(&rules), &defaults, false));
+        assert!(!is_default_run(&options, None, None, &defaults, true));
+        let mut extra = defaults.clone();
+        extra.extend(["-e".to_string(), "src/gen".to_string()]);
+        assert!(!is_default_run(&options, None, None, &extra, false));
+
+        for target in [
+            CloneTarget::Branch("dev".to_string()),
+            CloneTarget::Ref("v1.0".to_string()),
+            CloneTarget::Commit("0123456".to_string()),
+        ] {
+            let options = CloneOptions {
+                target,
+                ..Default::default()
+            };
+            assert!(!is_default_run(&options, None, None, &defaults, false));
+        }
+        let private = CloneOptions {
+            token: Some("secret".to_string()),
+            ..Default::default()
+        };
+        assert!(!is_default_run(&private, None, None, &defaults, false));
+        let submodules = CloneOptions {
+            submodules: true,
+            ..Default::default()
+        };
+        assert!(!is_default_run(&submodules, None, None, &defaults, false));
+    }
+
+    /// 临时目录中残留的克隆目录
+    fn clone_dirs(dir: &Path) -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .unwrap()
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.file_name().unwrap().to_string_lossy().starts_with("cjrepo_"))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn stalled_clone_times_out_and_cleans_up() {
+        let temp = tempfile::tempdir().unwrap();
+        let temp_dir = temp.path().to_string_lossy().to_string();
+        let server = hanging_server();
+        fake_redis().await;
+        let _env = set_env(&[
+            ("TEMP_DIR", Some(&temp_dir)),
+            ("REQUEST_TIMEOUT_SECONDS", Some("1")),
+            ("CLONE_RETRY_ATTEMPTS", Some("1")),
+            ("RATE_LIMIT_MAX_REQUESTS", Some("0")),
+            ("ALLOWED_REPO_HOSTS", Some("localhost")),
+        ])
+        .await;
+
+        // force 跳过远端提交查询，直接克隆不响应的远端
+        let query = format!("repo=https://localhost:{}/demo/stalled&force=true", server.port);
+        let response = handler(get(&query)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        let body = json_body(&response);
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error"], "request did not finish within 1 seconds");
+
+        // 远端断开后阻塞线程上的克隆结束，克隆目录随之删除
+        drop(server);
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !clone_dirs(temp.path()).is_empty() {
+            assert!(Instant::now() < deadline, "clone directory was not removed");
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    #[test]
+    fn depth_accepts_numbers_and_full() {
+        let depth = |raw: &str| {
+            let request = RefreshRequest {
+                depth: Some(raw.to_string()),
+                ..Default::default()
+            };
+            clone_options(&request, None).map(|options| options.depth)
+        };
+
+        assert_eq!(depth("1").unwrap(), Some(1));
+        assert_eq!(depth("25").unwrap(), Some(25));
+        assert_eq!(depth("0").unwrap(), Some(0));
+        assert_eq!(depth("full").unwrap(), Some(0));
+        assert!(matches!(depth("-1"), Err(RefreshError::InvalidParameter(_))));
+        assert_eq!(clone_options(&RefreshRequest::default(), None).unwrap().depth, None);
+    }
+
+    #[tokio::test]
+    async fn dry_run_reports_packages_without_writing_redis() {
+        let home = tempfile::tempdir().unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        let redis = fake_redis().await;
+        fake_cjlint(home.path(), Some("[]"), 0);
+        let repo = fixture_package("dry_run");
+        let _env = fixture_env(home.path(), temp.path()).await;
+
+        let response = handler(get(&format!("repo={}&dry_run=true&depth=full", repo)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(&response);
+        assert_eq!(body["data"]["package_name"], "demo");
+        assert_eq!(body["data"]["cj_file_count"], 1);
+        assert!(redis.keys().iter().all(|key| !key.contains("dry_run")));
+        assert_eq!(std::fs::read_dir(temp.path()).unwrap().count(), 0);
+        assert!(fake_cjlint_calls(home.path()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn mirrored_clone_is_cached_under_the_original_url() {
+        let home = tempfile::tempdir().unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        let redis = fake_redis().await;
+        fake_cjlint(home.path(), Some("[]"), 0);
+        fixture_package("mirrored");
+        let home_path = home.path().to_string_lossy().to_string();
+        let temp_path = temp.path().to_string_lossy().to_string();
+        let _env = set_env(&[
+            ("CANGJIE_HOME", Some(home_path.as_str())),
+            ("TEMP_DIR", Some(temp_path.as_str())),
+            ("ALLOWED_REPO_HOSTS", Some("origin.test")),
+            ("REPO_MIRRORS", Some(r#"{"origin.test": "fixture.test"}"#)),
+            ("RATE_LIMIT_MAX_REQUESTS", Some("0")),
+        ])
+        .await;
+
+        // 实际从镜像克隆，缓存键和结果仍使用原始地址
+        let repo = "https://origin.test/mirrored";
+        assert_eq!(normalize_clone_url(repo), "https://fixture.test/mirrored");
+        let response = handler(get(&format!("repo={}&depth=full", repo)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let commit = json_body(&response)["data"]["commit"].as_str().unwrap().to_string();
+
+        let keys: Vec<_> =
+            redis.keys().into_iter().filter(|key| key.contains("/mirrored")).collect();
+        assert!(keys.contains(&format!("cjlint_{}", repo)), "{:?}", keys);
+        assert!(keys.iter().any(|key| key.contains(&format!("{}_{}", repo, commit))));
+        assert!(keys.iter().all(|key| !key.contains("fixture.test")), "{:?}", keys);
+    }
+
+    #[tokio::test]
+    async fn subdir_limits_cjlint_and_keeps_repo_relative_paths() {
+        const REPORT: &str = r#"[{
+            "file": "{dir}/src/main.cj",
+            "line": 1,
+            "column": 1,
+            "endLine": 1,
+            "endColumn": 2,
+            "analyzerName": "G.FMT.01",
+            "description": "demo",
+            "defectLevel": "SUGGESTIONS",
+            "defectType": "FMT",
+            "language": "Cangjie"
+        }]"#;
+        let home = tempfile::tempdir().unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        fake_redis().await;
+        fake_cjlint(home.path(), Some(REPORT), 0);
+        let (repo, url) = fixture_repo("subdir");
+        commit_file(&repo, "cjpm.toml", "[package]\nname = \"demo\"\n");
+        commit_file(&repo, "src/main.cj", "main() {}\n");
+        commit_file(&repo, "pkg/src/main.cj", "main() {}\n");
+        let home_path = home.path().to_string_lossy().to_string();
+        let temp_path = temp.path().to_string_lossy().to_string();
+        let _env = set_env(&[
+            ("CANGJIE_HOME", Some(home_path.as_str())),
+            ("TEMP_DIR", Some(temp_path.as_str())),
+            ("ALLOWED_REPO_HOSTS", Some("fixture.test")),
+            ("RATE_LIMIT_MAX_REQUESTS", Some("0")),
+        ])
+        .await;
+
+        let response = handler(get(&format!("repo={}&depth=full&path=pkg", url)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let data = &json_body(&response)["data"];
+        assert_eq!(data["cjlint"].as_array().unwrap().len(), 1);
+        assert_eq!(data["cjlint"][0]["file"], "pkg/src/main.cj");
+        assert_eq!(data["path"], "pkg");
+        let calls = fake_cjlint_calls(home.path());
+        assert_eq!(calls.len(), 1);
+        assert!(calls[0].ends_with("/pkg"), "{:?}", calls);
+    }
+
+    const REPORT: &str = r#"[
+        {"file": "{dir}/src/main.cj", "line": 1, "column": 1, "endLine": 1, "endColumn": 2,
+         "analyzerName": "G.FMT.01", "description": "demo", "defectLevel": "SUGGESTIONS",
+         "defectType": "FMT", "language": "Cangjie"},
+        {"file": "{dir}/src/main.cj", "line": 1, "column": 1, "endLine": 1, "endColumn": 2,
+         "analyzerName": "G.NAM.02", "description": "demo", "defectLevel": "SUGGESTIONS",
+         "defectType": "NAM", "language": "Cangjie"}
+    ]"#;
+
+    #[tokio::test]
+    async fn severity_overrides_are_reflected_in_the_response() {
+        let home = tempfile::tempdir().unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        fake_cjlint(home.path(), Some(REPORT), 0);
+        fake_redis().await;
+        let repo = fixture_package("overrides");
+        let _env = fixture_env(home.path(), temp.path()).await;
+
+        let query = format!("repo={}&depth=full&severity_overrides=G.FMT.01:MANDATORY", repo);
+        let response = handler(get(&query)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let data = &json_body(&response)["data"];
+        assert_eq!(data["cjlint"][0]["analyzerName"], "G.FMT.01");
+        assert_eq!(data["cjlint"][0]["defectLevel"], "MANDATORY");
+        assert_eq!(data["cjlint"][0]["originalDefectLevel"], "SUGGESTIONS");
+        assert_eq!(data["cjlint"][1]["defectLevel"], "SUGGESTIONS");
+        assert!(data["cjlint"][1].get("originalDefectLevel").is_none());
+        assert_eq!(data["summary"]["mandatory"], 1);
+        assert_eq!(data["summary"]["suggestions"], 1);
+    }
+
+    #[tokio::test]
+    async fn blank_or_non_url_repo_is_rejected_before_any_work() {
+        let home = tempfile::tempdir().unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        let _env = fixture_env(home.path(), temp.path()).await;
+
+        for (query, error) in [
+            ("repo=", "repo query parameter is required"),
+            ("repo=%20%20%09", "repo query parameter is required"),
+            ("repo=not%20a%20url", "Invalid repo URL: relative URL without a base"),
+            ("repo=ftp://fixture.test/demo", "Invalid repo URL: "),
+        ] {
+            let response = handler(get(query)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", query);
+            let body = json_body(&response);
+            assert_eq!(body["success"], false);
+            assert!(body["error"].as_str().unwrap().starts_with(error), "{}", body);
+        }
+        assert_eq!(std::fs::read_dir(temp.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn raw_output_is_admin_only_and_unmodified() {
+        let home = tempfile::tempdir().unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        fake_cjlint(home.path(), Some(REPORT), 0);
+        fake_redis().await;
+        let repo = fixture_package("raw");
+        let home_path = home.path().to_string_lossy().to_string();
+        let temp_path = temp.path().to_string_lossy().to_string();
+        let _env = set_env(&[
+            ("CANGJIE_HOME", Some(home_path.as_str())),
+            ("TEMP_DIR", Some(temp_path.as_str())),
+            ("ALLOWED_REPO_HOSTS", Some("fixture.test")),
+            ("RATE_LIMIT_MAX_REQUESTS", Some("0")),
+            ("ADMIN_SECRET", Some("s3cret")),
+        ])
+        .await;
+
+        let uri = format!("/api/refresh?repo={}&depth=full&raw=true", repo);
+        let raw = |secret: Option<&str>| {
+            let mut builder = http::Request::builder().uri(&uri);
+            if let Some(secret) = secret {
+                builder = builder.header("X-Admin-Secret", secret);
+            }
+            handler(builder.body(Body::Empty).unwrap())
+        };
+
+        for secret in [None, Some("wrong")] {
+            let response = raw(secret).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+        assert!(fake_cjlint_calls(home.path()).is_empty());
+
+        let response = raw(Some("s3cret")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["Content-Type"], "application/json");
+        let body = match response.body() {
+            Body::Text(body) => body.clone(),
+            Body::Binary(body) => String::from_utf8(body.clone()).unwrap(),
+            Body::Empty => panic!("raw output should not be empty"),
+        };
+        // 报告原样返回，路径仍是克隆目录中的绝对路径
+        let lint_dir = &fake_cjlint_calls(home.path())[0];
+        assert_eq!(body, REPORT.replace("{dir}", lint_dir));
+    }
 }
@@ -0,0 +1,59 @@
+use cangjie_card::auth::authorize_admin;
+use cangjie_card::baseline::baseline_fingerprint;
+use cangjie_card::error::RefreshError;
+use cangjie_card::models::AnalysisResult;
+use cangjie_card::response::{create_error_response, create_response};
+use cangjie_card::storage::{get_from_redis, save_baseline};
+use cangjie_card::utils::{init_tracing, query_params};
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use tracing::info;
+use vercel_runtime::{run, Body, Error, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    init_tracing();
+    run(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    let hash_query = query_params(&req);
+
+    match set_baseline(&req, &hash_query).await {
+        Ok(data) => create_response(
+            StatusCode::OK,
+            true,
+            Some("Baseline saved"),
+            Some(data),
+            None,
+        ),
+        Err(e) => create_error_response(&e),
+    }
+}
+
+/// 将仓库最新的分析结果记为基线
+async fn set_baseline(
+    req: &Request,
+    hash_query: &HashMap<String, String>,
+) -> Result<Value, RefreshError> {
+    authorize_admin(req)?;
+
+    let repo = hash_query.get("repo").ok_or(RefreshError::MissingRepoParam)?;
+    let content = get_from_redis(repo).await?.ok_or(RefreshError::CacheMiss)?;
+    let analysis_result: AnalysisResult = serde_json::from_str(&content)?;
+
+    let fingerprints: Vec<String> = analysis_result
+        .cjlint
+        .iter()
+        .map(baseline_fingerprint)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    save_baseline(repo, &fingerprints).await?;
+    info!(fingerprints = fingerprints.len(), "Baseline saved");
+
+    Ok(json!({
+        "commit": analysis_result.commit,
+        "fingerprints": fingerprints.len(),
+    }))
+}
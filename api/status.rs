@@ -0,0 +1,44 @@
+use cangjie_card::error::RefreshError;
+use cangjie_card::jobs::Job;
+use cangjie_card::response::{
+    apply_cors, compress_response, create_error_response, create_preflight_response,
+    create_response,
+};
+use cangjie_card::utils::{init_tracing, query_params};
+use std::collections::HashMap;
+use http::Method;
+use vercel_runtime::{run, Body, Error, Request, Response, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    init_tracing();
+    run(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    if req.method() == Method::OPTIONS {
+        return create_preflight_response(&req);
+    }
+
+    let response = handle(&req).await?;
+    let response = compress_response(&req, response)?;
+    Ok(apply_cors(&req, response))
+}
+
+async fn handle(req: &Request) -> Result<Response<Body>, Error> {
+    let hash_query = query_params(req);
+
+    match get_job_status(&hash_query).await {
+        Ok(job) => create_response(StatusCode::OK, true, None, Some(job), None),
+        Err(e) => create_error_response(&e),
+    }
+}
+
+/// 按 id 参数读取后台刷新任务的状态
+async fn get_job_status(hash_query: &HashMap<String, String>) -> Result<Job, RefreshError> {
+    let id = hash_query
+        .get("id")
+        .ok_or_else(|| RefreshError::InvalidParameter("id query parameter is required".into()))?;
+
+    Job::load(id).await?.ok_or(RefreshError::JobNotFound)
+}
@@ -1,5 +1,6 @@
 use futures_util::StreamExt;
 use octocrab::Octocrab;
+use sha2::{Digest, Sha256};
 use std::env;
 use std::fs;
 use std::io::Write;
@@ -57,9 +58,31 @@ fn generate_include_code(
     Ok(())
 }
 
+/// 计算压缩包的SHA-256并通过环境变量提供给 lib/utils.rs，运行时以此校验内嵌的压缩包
+fn emit_archive_checksum(archive: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut fs::File::open(archive)?, &mut hasher)?;
+    println!(
+        "cargo:rustc-env=CJLINT_ARCHIVE_SHA256={:x}",
+        hasher.finalize()
+    );
+
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("cargo:rerun-if-changed=build.rs");
+    // 压缩包的期望SHA-256，由 lib/utils.rs 通过 option_env! 读取
+    println!("cargo:rerun-if-env-changed=CJLINT_SHA256");
+
+    let owner = "ZxillyLib";
+    let repo = "cangjie-card-bin";
+    let tag = "0.58.3";
+    let asset_name = "cjlint.tar.zst";
+
+    // 无法通过 cjlint --version 获取版本时使用
+    println!("cargo:rustc-env=CJLINT_ARCHIVE_VERSION={}", tag);
 
     let out_dir = env::var("OUT_DIR").unwrap();
     let output_file = Path::new(&out_dir).join("cjlint.tar.zst");
@@ -70,6 +93,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         if existing_path.exists() {
             // 使用已有文件生成包含代码
             generate_include_code(existing_path, &include_file)?;
+            emit_archive_checksum(existing_path)?;
             println!(
                 "cargo:rustc-env=CJLINT_DATA_FILE={}",
                 include_file.display()
@@ -79,6 +103,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     if output_file.exists() && include_file.exists() {
+        emit_archive_checksum(&output_file)?;
         println!(
             "cargo:rustc-env=CJLINT_DATA_FILE={}",
             include_file.display()
@@ -86,11 +111,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
-    let owner = "ZxillyLib";
-    let repo = "cangjie-card-bin";
-    let tag = "0.58.3";
-    let asset_name = "cjlint.tar.zst";
-
     let token = env::var("GH_TOKEN").expect("GH_TOKEN environment variable not set");
 
     let octocrab = Octocrab::builder().personal_token(token).build()?;
@@ -98,6 +118,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     download_github_asset(&octocrab, owner, repo, tag, asset_name, &output_file).await?;
 
     generate_include_code(&output_file, &include_file)?;
+    emit_archive_checksum(&output_file)?;
 
     println!(
         "cargo:rustc-env=CJLINT_DATA_FILE={}",
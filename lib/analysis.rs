@@ -1,82 +1,1513 @@
-use std::process::Command;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::path::{Component, Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use glob::{MatchOptions, Pattern, PatternError};
 use tokio::fs;
-use vercel_runtime::Error;
-use crate::models::AnalysisResultItem;
-use crate::utils::{generate_random_string, get_memory_usage};
+use tokio::process::Command;
+use tokio::sync::{OnceCell, Semaphore};
+use tokio::task::JoinSet;
+use tokio::time::timeout;
+use tracing::{info, instrument, warn};
+use crate::config::{
+    cangjie_home, cjlint_bin, cjlint_library_paths, cjlint_timeout_seconds, max_findings,
+    max_parallel_lints, temp_dir,
+};
+use crate::error::RefreshError;
+use crate::models::{
+    AnalysisResult, AnalysisResultItem, AnalysisSummary, CjlintRun, DefectLevel, DiffSummary,
+    IssueCount, LevelFilter, PageInfo, Pagination, SortOrder,
+};
+use crate::utils::{create_temp_dir, get_memory_usage};
+
+/// 允许透传给cjlint的参数，每个参数都需要一个取值：
+/// - `-j <n>`：并行分析的线程数，必须为正整数
+/// - `-e <pattern>`：排除匹配该模式的路径
+///
+/// `-f`、`-r`、`-o` 由服务端控制，不允许覆盖
+const ALLOWED_EXTRA_ARGS: &[&str] = &["-j", "-e"];
+
+/// 解析并校验以空白分隔的额外cjlint参数
+pub fn parse_extra_args(raw: &str) -> Result<Vec<String>, RefreshError> {
+    let mut args = Vec::new();
+    let mut tokens = raw.split_whitespace();
+
+    while let Some(flag) = tokens.next() {
+        if !ALLOWED_EXTRA_ARGS.contains(&flag) {
+            return Err(RefreshError::InvalidParameter(format!(
+                "cjlint argument {} is not allowed, allowed arguments: {}",
+                flag,
+                ALLOWED_EXTRA_ARGS.join(", ")
+            )));
+        }
+
+        let value = tokens
+            .next()
+            .filter(|value| !value.starts_with('-'))
+            .ok_or_else(|| {
+                RefreshError::InvalidParameter(format!("cjlint argument {} requires a value", flag))
+            })?;
+
+        if flag == "-j" && !value.parse::<u32>().is_ok_and(|threads| threads > 0) {
+            return Err(RefreshError::InvalidParameter(format!(
+                "cjlint argument -j expects a positive integer, got {}",
+                value
+            )));
+        }
+
+        args.push(flag.to_string());
+        args.push(value.to_string());
+    }
+
+    Ok(args)
+}
+
+// 内嵌压缩包对应的工具链版本，由 build.rs 提供
+const CJLINT_ARCHIVE_VERSION: &str = env!("CJLINT_ARCHIVE_VERSION");
+
+// 查询版本号的最长时间
+const CJLINT_VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+// 同一进程内只查询一次cjlint版本
+static CJLINT_VERSION: OnceCell<String> = OnceCell::const_new();
+
+/// 获取cjlint的版本号，`--version` 不可用或无法解析时使用内嵌压缩包的版本
+pub async fn cjlint_version() -> String {
+    CJLINT_VERSION
+        .get_or_init(|| async {
+            match query_cjlint_version().await {
+                Some(version) => version,
+                None => {
+                    warn!("Failed to query cjlint version, falling back to archive version");
+                    CJLINT_ARCHIVE_VERSION.to_string()
+                }
+            }
+        })
+        .await
+        .clone()
+}
+
+/// 创建运行cjlint的命令，工具链路径和运行时环境变量都来自 `CANGJIE_HOME`
+fn cjlint_command() -> Command {
+    let home = cangjie_home();
+    let library_path = cjlint_library_path(
+        &home,
+        &cjlint_library_paths(),
+        env::var_os("LD_LIBRARY_PATH").as_deref(),
+    );
+    let mut command = Command::new(cjlint_bin());
+    command
+        .env("LD_LIBRARY_PATH", library_path)
+        .env("CANGJIE_HOME", &home)
+        .kill_on_drop(true);
+    command
+}
+
+/// 组合传给cjlint的 `LD_LIBRARY_PATH`，按以下顺序查找：
+/// - 工具链自带的共享库目录（解压目录本身及运行时库目录）
+/// - CJLINT_LIBRARY_PATH 中额外配置的目录
+/// - 服务进程继承的 `LD_LIBRARY_PATH`
+fn cjlint_library_path(home: &Path, extra: &[PathBuf], inherited: Option<&OsStr>) -> OsString {
+    let mut paths = vec![home.to_path_buf(), home.join("runtime/lib/linux_x86_64_llvm")];
+    paths.extend(extra.iter().cloned());
+    if let Some(inherited) = inherited {
+        paths.extend(env::split_paths(inherited).filter(|p| !p.as_os_str().is_empty()));
+    }
+
+    // 目录中包含分隔符时无法拼接，退回只使用工具链目录
+    env::join_paths(&paths).unwrap_or_else(|e| {
+        warn!("Failed to compose LD_LIBRARY_PATH: {}", e);
+        home.as_os_str().to_os_string()
+    })
+}
+
+/// 运行 `cjlint --version` 并从输出中解析版本号
+async fn query_cjlint_version() -> Option<String> {
+    let output = cjlint_command().arg("--version").output();
+    let output = timeout(CJLINT_VERSION_TIMEOUT, output).await.ok()?.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    parse_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// 从版本输出中取出第一个形如 `0.58.3` 的版本号
+fn parse_version(output: &str) -> Option<String> {
+    output
+        .split(|c: char| c.is_whitespace() || c == ',' || c == ':')
+        .map(|token| token.trim_start_matches(['v', 'V']))
+        .find(|token| {
+            let mut parts = token.split('.');
+            parts.clone().count() >= 2
+                && parts.all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+        })
+        .map(str::to_string)
+}
 
 /// 运行cjlint工具分析代码
-pub async fn run_cjlint(repo_path: String) -> Result<String, Error> {
-    let output_path = format!("/tmp/{}.json", generate_random_string(10));
+///
+/// cjlint非零退出但仍写出了有效报告时返回该报告，并带上退出码
+#[instrument(skip(extra_args))]
+pub async fn run_cjlint(
+    repo_path: String,
+    extra_args: &[String],
+) -> Result<CjlintRun, RefreshError> {
+    let (items, exit_code) = execute_cjlint(repo_path, extra_args, parse_cjlint_output).await?;
+    Ok(CjlintRun { items, exit_code })
+}
+
+/// 运行cjlint并原样返回它写出的报告内容，不做任何解析，用于排查输出格式的变化
+///
+/// 只要报告存在就会返回，即使cjlint非零退出
+#[instrument(skip(extra_args))]
+pub async fn run_cjlint_raw(
+    repo_path: String,
+    extra_args: &[String],
+) -> Result<String, RefreshError> {
+    let (content, _) = execute_cjlint(repo_path, extra_args, |content| Ok(content.to_string()))
+        .await?;
+    Ok(content)
+}
+
+/// 运行cjlint并用 `parse` 读取报告，返回解析结果和退出码
+async fn execute_cjlint<T>(
+    repo_path: String,
+    extra_args: &[String],
+    parse: fn(&str) -> Result<T, RefreshError>,
+) -> Result<(T, i32), RefreshError> {
+    // 输出文件放在独立的临时目录中，任何返回路径上都会随 output_dir 一起删除
+    let output_dir = create_temp_dir("cjlint_")?;
+    let output_path = output_dir.path().join("output.json").to_string_lossy().to_string();
 
     // 使用函数获取并打印当前内存占用
     match get_memory_usage() {
         Ok(mem_info) => {
-            eprintln!("Current memory usage before running cjlint:\n{}", mem_info);
+            info!("Current memory usage before running cjlint:\n{}", mem_info);
         },
         Err(e) => {
-            eprintln!("Failed to get memory usage: {}", e);
+            warn!("Failed to get memory usage: {}", e);
         }
     }
 
-    let output = Command::new("/tmp/cj/tools/bin/cjlint")
-        .args(&["-f", &repo_path, "-r", "json", "-o", &output_path])
-        .env("LD_LIBRARY_PATH", "/tmp/cj")
-        .env("CANGJIE_HOME", "/tmp/cj")
-        .output()
-        .map_err(|e| Error::from(format!("Failed to execute cjlint: {}", e)))?;
+    // kill_on_drop 保证超时丢弃 future 时子进程会被杀掉
+    let child = cjlint_command()
+        .args(["-f", &repo_path, "-r", "json", "-o", &output_path])
+        .args(extra_args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| RefreshError::CjlintFailed {
+            code: -1,
+            output: format!("Failed to execute cjlint: {}", e),
+        })?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-    let combined_output = format!("STDOUT:\n{}\nSTDERR:\n{}", stdout, stderr);
+    let timeout_seconds = cjlint_timeout_seconds();
+    let wait = timeout(Duration::from_secs(timeout_seconds), child.wait_with_output());
+    let output = match wait.await {
+        Ok(result) => result.map_err(|e| RefreshError::CjlintFailed {
+            code: -1,
+            output: format!("Failed to wait for cjlint: {}", e),
+        })?,
+        Err(_) => {
+            return Err(RefreshError::CjlintTimeout {
+                seconds: timeout_seconds,
+            });
+        }
+    };
 
-    if !output.status.success() {
-        return Err(Error::from(format!(
-            "cjlint command failed with exit code: {}\n{}",
-            output.status.code().unwrap_or(-1),
-            combined_output
-        )));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let combined_output = diagnostic_output(
+        &stdout,
+        &stderr,
+        &[
+            (output_dir.path().to_string_lossy().as_ref(), "<output>"),
+            (repo_path.as_str(), "<repo>"),
+            (cangjie_home().to_string_lossy().as_ref(), "<cangjie_home>"),
+            (temp_dir().as_str(), "<tmp>"),
+        ],
+    );
+
+    let exit_code = output.status.code().unwrap_or(-1);
+    let json_content = fs::read_to_string(&output_path).await;
+
+    if output.status.success() {
+        let json_content = json_content.map_err(|source| RefreshError::CjlintOutputMissing {
+            source,
+            output: combined_output,
+        })?;
+        return Ok((parse(&json_content)?, exit_code));
     }
 
-    let json_content = match fs::read_to_string(&output_path).await {
-        Ok(content) => content,
-        Err(e) => {
-            return Err(Error::from(format!(
-                "Failed to read cjlint output: {}\n{}",
-                e, combined_output
+    // 只有报告缺失或无法解析时才视为失败
+    match json_content
+        .ok()
+        .and_then(|content| parse(&content).ok())
+    {
+        Some(parsed) => {
+            warn!(exit_code, "cjlint exited with non-zero code but produced a report");
+            Ok((parsed, exit_code))
+        }
+        None => {
+            warn!(exit_code, "cjlint failed\nSTDOUT:\n{}\nSTDERR:\n{}", stdout, stderr);
+            Err(RefreshError::CjlintFailed {
+                code: exit_code,
+                output: combined_output,
+            })
+        }
+    }
+}
+
+// 对象包装的报告中可能存放问题列表的字段，按顺序查找
+const CJLINT_RESULT_FIELDS: &[&str] = &["results", "defects", "issues", "cjlint"];
+
+/// 解析cjlint的JSON报告，兼容不同版本的两种结构：
+/// - 问题数组 `[{...}, ...]`
+/// - 包装在对象中的问题数组，如 `{"results": [{...}, ...]}`
+///
+/// 问题中未知的字段被忽略，可选字段缺失时使用默认值，结构不符合预期时返回 502
+pub fn parse_cjlint_output(content: &str) -> Result<Vec<AnalysisResultItem>, RefreshError> {
+    let value: serde_json::Value = serde_json::from_str(content)
+        .map_err(|e| RefreshError::CjlintOutputInvalid(format!("not valid JSON: {}", e)))?;
+
+    let items = match value {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Object(mut object) => {
+            let field = CJLINT_RESULT_FIELDS
+                .iter()
+                .find(|field| object.get(**field).is_some_and(|value| value.is_array()));
+            match field.and_then(|field| object.remove(*field)) {
+                Some(serde_json::Value::Array(items)) => items,
+                _ => {
+                    let keys: Vec<&str> = object.keys().map(String::as_str).collect();
+                    return Err(RefreshError::CjlintOutputInvalid(format!(
+                        "expected an array of findings, got an object with keys [{}]",
+                        keys.join(", ")
+                    )));
+                }
+            }
+        }
+        other => {
+            return Err(RefreshError::CjlintOutputInvalid(format!(
+                "expected an array of findings, got {}",
+                json_type_name(&other)
             )));
         }
     };
 
-    if let Err(e) = fs::remove_file(&output_path).await {
-        eprintln!("Warning: Failed to delete cjlint output file: {}", e);
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(index, item)| {
+            serde_json::from_value(item).map_err(|e| {
+                RefreshError::CjlintOutputInvalid(format!("finding {}: {}", index, e))
+            })
+        })
+        .collect()
+}
+
+fn json_type_name(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "a boolean",
+        serde_json::Value::Number(_) => "a number",
+        serde_json::Value::String(_) => "a string",
+        serde_json::Value::Array(_) => "an array",
+        serde_json::Value::Object(_) => "an object",
+    }
+}
+
+// 错误信息中保留的cjlint输出长度（字节）
+const MAX_DIAGNOSTIC_BYTES: usize = 4096;
+
+/// 生成返回给调用方的cjlint输出：优先使用stderr，只保留末尾部分，并隐藏服务器上的绝对路径
+///
+/// `redactions` 按顺序替换，较长的路径需要排在其前缀之前
+fn diagnostic_output(stdout: &str, stderr: &str, redactions: &[(&str, &str)]) -> String {
+    let (name, content) = if stderr.trim().is_empty() {
+        ("STDOUT", stdout)
+    } else {
+        ("STDERR", stderr)
+    };
+
+    let mut redacted = content.trim_end().to_string();
+    for (path, placeholder) in redactions {
+        if !path.is_empty() {
+            redacted = redacted.replace(path, placeholder);
+        }
     }
 
-    Ok(json_content)
+    if redacted.len() <= MAX_DIAGNOSTIC_BYTES {
+        return format!("{}:\n{}", name, redacted);
+    }
+    let mut start = redacted.len() - MAX_DIAGNOSTIC_BYTES;
+    while !redacted.is_char_boundary(start) {
+        start += 1;
+    }
+    format!("{} (truncated):\n...{}", name, &redacted[start..])
 }
 
-/// 处理分析结果，移除文件路径中的仓库路径前缀
+/// 找出需要单独运行cjlint的包目录，嵌套在其他包内的目录会随外层包一起检查
+pub fn lint_roots(packages: &[(PathBuf, String)]) -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = Vec::new();
+    // packages 已按目录深度排序，外层的包总是先出现
+    for (package_dir, _) in packages {
+        if !roots.iter().any(|root| package_dir.starts_with(root)) {
+            roots.push(package_dir.clone());
+        }
+    }
+    roots
+}
+
+/// 并发地对多个目录运行cjlint并合并结果，并发数量受 MAX_PARALLEL_LINTS 限制
+///
+/// 任一目录失败时返回错误，多个目录非零退出时使用第一个非零的退出码
+pub async fn run_cjlint_parallel(
+    lint_dirs: Vec<PathBuf>,
+    extra_args: &[String],
+) -> Result<CjlintRun, RefreshError> {
+    let semaphore = Arc::new(Semaphore::new(max_parallel_lints()));
+    let extra_args: Arc<[String]> = extra_args.into();
+
+    // JoinSet 丢弃时中止其余任务，某个目录失败后不会留下仍在运行的cjlint
+    let mut tasks = JoinSet::new();
+    let lint_count = lint_dirs.len();
+    for (index, lint_dir) in lint_dirs.into_iter().enumerate() {
+        let semaphore = semaphore.clone();
+        let extra_args = extra_args.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.map_err(|e| {
+                RefreshError::CjlintFailed {
+                    code: -1,
+                    output: format!("Failed to acquire lint slot: {}", e),
+                }
+            })?;
+            let cjlint_run = run_cjlint(lint_dir.to_string_lossy().to_string(), &extra_args).await?;
+            Ok::<_, RefreshError>((index, cjlint_run))
+        });
+    }
+
+    // 按目录顺序合并结果，与完成顺序无关
+    let mut runs: Vec<Option<CjlintRun>> = (0..lint_count).map(|_| None).collect();
+    while let Some(joined) = tasks.join_next().await {
+        let (index, cjlint_run) = joined.map_err(|e| RefreshError::CjlintFailed {
+            code: -1,
+            output: format!("cjlint task failed: {}", e),
+        })??;
+        runs[index] = Some(cjlint_run);
+    }
+
+    let mut merged = CjlintRun {
+        items: Vec::new(),
+        exit_code: 0,
+    };
+    for cjlint_run in runs.into_iter().flatten() {
+        merged.items.extend(cjlint_run.items);
+        if merged.exit_code == 0 {
+            merged.exit_code = cjlint_run.exit_code;
+        }
+    }
+
+    Ok(merged)
+}
+
+/// 处理分析结果，移除文件路径中的仓库路径前缀，原始路径保存在 `absolute_path` 中
 pub fn process_analysis_result(
     analysis_result: Vec<AnalysisResultItem>,
     repo_path: &str,
 ) -> Vec<AnalysisResultItem> {
-    let repo_path_with_slash = if repo_path.ends_with('/') {
-        repo_path.to_string()
-    } else {
-        format!("{}/", repo_path)
-    };
-    
     analysis_result
         .into_iter()
         .map(|mut item| {
-            if item.file.starts_with(&repo_path_with_slash) {
-                item.file = item.file[repo_path_with_slash.len()..].to_string();
-            } else if item.file.starts_with(repo_path) {
-                item.file = item.file[repo_path.len()..].to_string();
-                if item.file.starts_with('/') {
-                    item.file = item.file[1..].to_string();
+            match relative_path(&item.file, repo_path) {
+                Some(relative) => {
+                    item.absolute_path = Some(std::mem::replace(&mut item.file, relative));
                 }
+                None => item.file = normalize_separators(&item.file),
             }
             item
         })
         .collect()
-} 
\ No newline at end of file
+}
+
+/// 计算文件相对仓库目录的路径，文件不在仓库内时返回 None
+///
+/// 按路径组件比较，因此不受结尾斜杠和 `./` 的影响，也不会把 `/tmp/a` 当作 `/tmp/ab` 的前缀；
+/// 比较前统一使用 `/` 作为分隔符，返回的路径同样只包含 `/`
+fn relative_path(file: &str, repo_path: &str) -> Option<String> {
+    let file = normalize_separators(file);
+    let repo_path = normalize_separators(repo_path);
+    let file = Path::new(file.strip_prefix("./").unwrap_or(&file));
+    let repo_path = Path::new(repo_path.strip_prefix("./").unwrap_or(&repo_path));
+
+    let relative = file.strip_prefix(repo_path).ok()?;
+
+    Some(relative.to_string_lossy().to_string())
+}
+
+/// 将 Windows 风格的 `\` 分隔符替换为 `/`
+fn normalize_separators(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+/// 根据文件路径为每个问题标注所属的包，嵌套的包优先
+pub fn assign_packages(
+    analysis_result: &mut [AnalysisResultItem],
+    packages: &[(PathBuf, String)],
+    repo_path: &str,
+) {
+    for item in analysis_result.iter_mut() {
+        let file = Path::new(repo_path).join(&item.file);
+        item.package = packages
+            .iter()
+            .filter(|(package_dir, _)| file.starts_with(package_dir))
+            .max_by_key(|(package_dir, _)| package_dir.components().count())
+            .map(|(_, package_name)| package_name.clone());
+    }
+}
+
+// 单个问题附带的源码片段的最大长度，超出部分截断
+const MAX_SNIPPET_CHARS: usize = 2000;
+
+/// 读取问题所在的 `line..=endLine` 行源码附加到问题上
+///
+/// 文件不在仓库内、无法读取或行号超出文件范围时不附加片段
+pub async fn attach_snippets(analysis_result: &mut [AnalysisResultItem], repo_path: &str) {
+    let mut files: HashMap<String, Option<String>> = HashMap::new();
+    for item in analysis_result.iter_mut() {
+        if !files.contains_key(&item.file) {
+            let path = Path::new(&item.file);
+            let content = if path.is_relative()
+                && path.components().all(|c| matches!(c, Component::Normal(_)))
+            {
+                fs::read_to_string(Path::new(repo_path).join(path)).await.ok()
+            } else {
+                None
+            };
+            files.insert(item.file.clone(), content);
+        }
+
+        item.snippet = files[&item.file]
+            .as_deref()
+            .and_then(|content| extract_snippet(content, item.line, item.end_line));
+    }
+}
+
+/// 截取从1开始编号的 `line..=end_line` 行，结束行早于起始行时只取起始行
+fn extract_snippet(content: &str, line: i32, end_line: i32) -> Option<String> {
+    let start = usize::try_from(line).ok().filter(|&line| line > 0)?;
+    let end = usize::try_from(end_line).unwrap_or(0).max(start);
+    let lines: Vec<&str> = content.lines().skip(start - 1).take(end - start + 1).collect();
+    if lines.is_empty() {
+        return None;
+    }
+
+    let snippet = lines.join("\n");
+    Some(match snippet.char_indices().nth(MAX_SNIPPET_CHARS) {
+        Some((offset, _)) => snippet[..offset].to_string(),
+        None => snippet,
+    })
+}
+
+/// 解析 .cjlintignore 内容，规则与 .gitignore 类似：
+/// - 空行和以 `#` 开头的行被忽略
+/// - 以 `/` 开头或中间包含 `/` 的模式相对仓库根目录匹配，否则匹配任意层级
+/// - 以 `/` 结尾的模式匹配目录，目录下的所有文件都会被排除
+///
+/// 不支持以 `!` 开头的反向规则
+pub fn parse_ignore_file(content: &str) -> Vec<Pattern> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            if line.starts_with('!') {
+                warn!(pattern = line, "Negated .cjlintignore patterns are not supported");
+                return None;
+            }
+
+            match path_pattern(line) {
+                Ok(pattern) => Some(pattern),
+                Err(e) => {
+                    warn!(pattern = line, "Invalid .cjlintignore pattern: {}", e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// 按 .cjlintignore 的规则编译单个路径模式
+fn path_pattern(raw: &str) -> Result<Pattern, PatternError> {
+    let pattern = raw.trim_end_matches('/');
+    let pattern = match pattern.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if pattern.contains('/') => pattern.to_string(),
+        None => format!("**/{}", pattern),
+    };
+    Pattern::new(&pattern)
+}
+
+/// 文件本身或它的任意一级父目录是否匹配其中一个模式
+fn matches_path(file: &str, patterns: &[Pattern]) -> bool {
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..Default::default()
+    };
+    Path::new(file).ancestors().any(|path| {
+        !path.as_os_str().is_empty()
+            && patterns
+                .iter()
+                .any(|pattern| pattern.matches_path_with(path, options))
+    })
+}
+
+/// 移除文件或其所在目录匹配忽略规则的问题，返回移除的数量
+pub fn apply_ignore_patterns(
+    analysis_result: &mut Vec<AnalysisResultItem>,
+    patterns: &[Pattern],
+) -> usize {
+    if patterns.is_empty() {
+        return 0;
+    }
+
+    let total = analysis_result.len();
+    analysis_result.retain(|item| !matches_path(&item.file, patterns));
+
+    total - analysis_result.len()
+}
+
+/// 只保留位于 `changed` 中的文件里的问题，返回移除的数量
+pub fn filter_to_changed(
+    analysis_result: &mut Vec<AnalysisResultItem>,
+    changed: &BTreeSet<String>,
+) -> usize {
+    let total = analysis_result.len();
+    analysis_result.retain(|item| changed.contains(&item.file));
+    total - analysis_result.len()
+}
+
+/// 解析以逗号分隔的路径模式，规则与 .cjlintignore 相同，`name` 为参数名
+pub fn parse_path_patterns(raw: &str, name: &str) -> Result<Vec<Pattern>, RefreshError> {
+    parse_list(raw)
+        .iter()
+        .map(|pattern| {
+            path_pattern(pattern).map_err(|e| {
+                RefreshError::InvalidParameter(format!(
+                    "Invalid pattern in {}: {}: {}",
+                    name, pattern, e
+                ))
+            })
+        })
+        .collect()
+}
+
+/// 只保留匹配 `include` 且不匹配 `exclude` 的文件中的问题，`include` 为空时不限制
+///
+/// 返回被排除的文件数量和问题数量
+pub fn filter_by_paths(
+    analysis_result: &mut Vec<AnalysisResultItem>,
+    include: &[Pattern],
+    exclude: &[Pattern],
+) -> (usize, usize) {
+    if include.is_empty() && exclude.is_empty() {
+        return (0, 0);
+    }
+
+    let total = analysis_result.len();
+    let mut excluded_files = HashSet::new();
+    analysis_result.retain(|item| {
+        let keep = (include.is_empty() || matches_path(&item.file, include))
+            && !matches_path(&item.file, exclude);
+        if !keep {
+            excluded_files.insert(item.file.clone());
+        }
+        keep
+    });
+
+    (excluded_files.len(), total - analysis_result.len())
+}
+
+/// 合并文件、位置、检查器和描述都相同的重复问题，保留第一次出现的位置
+///
+/// 被合并的问题在保留项的 `count` 中记录出现次数，返回被移除的重复项数量
+pub fn dedup_findings(analysis_result: &mut Vec<AnalysisResultItem>) -> usize {
+    let total = analysis_result.len();
+    let mut first_seen: HashMap<(String, i32, i32, String, String), usize> = HashMap::new();
+    let mut deduped: Vec<AnalysisResultItem> = Vec::with_capacity(total);
+
+    for item in analysis_result.drain(..) {
+        let key = (
+            item.file.clone(),
+            item.line,
+            item.column,
+            item.analyzer_name.clone(),
+            item.description.clone(),
+        );
+        match first_seen.get(&key) {
+            Some(&index) => {
+                let kept = &mut deduped[index];
+                kept.count = Some(kept.count.unwrap_or(1) + 1);
+            }
+            None => {
+                first_seen.insert(key, deduped.len());
+                deduped.push(item);
+            }
+        }
+    }
+
+    *analysis_result = deduped;
+    total - analysis_result.len()
+}
+
+// 统计中最多列出的检查器与问题类型组合数量
+const TOP_ISSUES_LIMIT: usize = 10;
+
+/// 统计分析结果中各级别、各检查器及各问题类型的问题数量
+///
+/// 同时在一次遍历中统计检查器与问题类型的组合，按数量从多到少取前
+/// `TOP_ISSUES_LIMIT` 个，数量相同时按检查器名和问题类型排序
+pub fn summarize(analysis_result: &[AnalysisResultItem]) -> AnalysisSummary {
+    let mut summary = AnalysisSummary {
+        total: analysis_result.len(),
+        ..Default::default()
+    };
+    let mut issues: HashMap<(&str, &str), usize> = HashMap::new();
+
+    for item in analysis_result {
+        match item.defect_level {
+            DefectLevel::Mandatory => summary.mandatory += 1,
+            DefectLevel::Suggestions => summary.suggestions += 1,
+        }
+        *summary
+            .by_analyzer
+            .entry(item.analyzer_name.clone())
+            .or_insert(0) += 1;
+        *summary
+            .by_type
+            .entry(item.defect_type.clone())
+            .or_insert(0) += 1;
+        *issues
+            .entry((item.analyzer_name.as_str(), item.defect_type.as_str()))
+            .or_insert(0) += 1;
+    }
+
+    let mut issues: Vec<_> = issues.into_iter().collect();
+    issues.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+    summary.top_issues = issues
+        .into_iter()
+        .take(TOP_ISSUES_LIMIT)
+        .map(|((analyzer, defect_type), count)| IssueCount {
+            analyzer: analyzer.to_string(),
+            defect_type: defect_type.to_string(),
+            count,
+        })
+        .collect();
+
+    summary
+}
+
+/// 按指定顺序重新排列问题，默认的位置顺序与分析时的排序一致
+pub fn sort_by_order(analysis_result: &mut [AnalysisResultItem], order: SortOrder) {
+    match order {
+        SortOrder::Position => sort_findings(analysis_result),
+        SortOrder::Severity => {
+            // 排序是稳定的，先按位置排序即可作为同级别内的次序
+            sort_findings(analysis_result);
+            analysis_result.sort_by_key(|item| match item.defect_level {
+                DefectLevel::Mandatory => 0,
+                DefectLevel::Suggestions => 1,
+            });
+        }
+    }
+}
+
+/// 每千行代码的问题数量，保留两位小数，没有源码时返回 None
+pub fn defect_density(findings: usize, lines: usize) -> Option<f64> {
+    if lines == 0 {
+        return None;
+    }
+    let density = findings as f64 * 1000.0 / lines as f64;
+    Some((density * 100.0).round() / 100.0)
+}
+
+/// 按文件、行、列和检查器排序，保证相同输入的输出顺序一致
+pub fn sort_findings(analysis_result: &mut [AnalysisResultItem]) {
+    analysis_result.sort_by(|a, b| {
+        (&a.file, a.line, a.column, &a.analyzer_name).cmp(&(
+            &b.file,
+            b.line,
+            b.column,
+            &b.analyzer_name,
+        ))
+    });
+}
+
+/// 按问题的当前顺序截取一页，并在结果中记录分页信息
+///
+/// 调用方负责事先排序，分析结果默认已按位置排序；offset 超出范围时返回空列表
+pub fn paginate(analysis_result: &mut AnalysisResult, pagination: Pagination) {
+    let items = &mut analysis_result.cjlint;
+    let total = items.len();
+    let start = pagination.offset.min(total);
+    let end = match pagination.limit {
+        Some(limit) => start.saturating_add(limit).min(total),
+        None => total,
+    };
+    items.truncate(end);
+    items.drain(..start);
+
+    analysis_result.page = Some(PageInfo {
+        total,
+        offset: pagination.offset,
+        limit: pagination.limit,
+        next_offset: (end < total).then_some(end),
+    });
+}
+
+/// 计算实际生效的问题数量上限，请求只能在 MAX_FINDINGS 的基础上调低，0 表示不限制
+pub fn effective_max_findings(requested: Option<&str>) -> Result<usize, RefreshError> {
+    let configured = max_findings();
+    let requested = match requested {
+        None => return Ok(configured),
+        Some(raw) => match raw.trim().parse::<usize>() {
+            Ok(max) if max > 0 => max,
+            _ => {
+                return Err(RefreshError::InvalidParameter(format!(
+                    "max_findings expects a positive integer, got {}",
+                    raw
+                )));
+            }
+        },
+    };
+
+    Ok(match configured {
+        0 => requested,
+        configured => requested.min(configured),
+    })
+}
+
+/// 按问题的当前顺序只保留前 max 个，发生截断时记录截断前的数量
+pub fn truncate_findings(analysis_result: &mut AnalysisResult, max: usize) {
+    let total = analysis_result.cjlint.len();
+    if max == 0 || total <= max {
+        return;
+    }
+
+    analysis_result.cjlint.truncate(max);
+    analysis_result.truncated = true;
+    analysis_result.total_before_truncation = Some(total);
+}
+
+/// 只保留指定语言的问题，语言名称不区分大小写
+pub fn filter_by_language(analysis_result: &mut Vec<AnalysisResultItem>, language: &str) {
+    let language = language.trim();
+    analysis_result.retain(|item| item.language.eq_ignore_ascii_case(language));
+}
+
+/// 按问题级别过滤分析结果
+pub fn filter_by_level(analysis_result: &mut Vec<AnalysisResultItem>, level: LevelFilter) {
+    match level {
+        LevelFilter::All => {}
+        LevelFilter::Mandatory => {
+            analysis_result.retain(|item| item.defect_level == DefectLevel::Mandatory)
+        }
+        LevelFilter::Suggestions => {
+            analysis_result.retain(|item| item.defect_level == DefectLevel::Suggestions)
+        }
+    }
+}
+
+/// 解析逗号分隔的列表参数，忽略空项
+pub fn parse_list(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// 解析 `检查器:级别` 形式、以逗号分隔的级别覆盖配置，级别为 mandatory 或 suggestions
+pub fn parse_severity_overrides(raw: &str) -> Result<HashMap<String, DefectLevel>, RefreshError> {
+    parse_list(raw)
+        .iter()
+        .map(|entry| {
+            let invalid = || {
+                RefreshError::InvalidParameter(format!(
+                    "Invalid severity override: {}, expected analyzer:mandatory or \
+                     analyzer:suggestions",
+                    entry
+                ))
+            };
+            let (analyzer, level) = entry.split_once(':').ok_or_else(invalid)?;
+            let level = match level.trim().to_ascii_lowercase().as_str() {
+                "mandatory" => DefectLevel::Mandatory,
+                "suggestions" => DefectLevel::Suggestions,
+                _ => return Err(invalid()),
+            };
+            Ok((analyzer.trim().to_string(), level))
+        })
+        .collect()
+}
+
+/// 按检查器强制修改问题级别，原级别保存在 `original_defect_level` 中，并同步更新统计
+pub fn apply_severity_overrides(
+    analysis_result: &mut AnalysisResult,
+    overrides: &HashMap<String, DefectLevel>,
+) {
+    for item in analysis_result.cjlint.iter_mut() {
+        let Some(&level) = overrides.get(&item.analyzer_name) else {
+            continue;
+        };
+        if level == item.defect_level {
+            continue;
+        }
+
+        let summary = &mut analysis_result.summary;
+        match level {
+            DefectLevel::Mandatory => {
+                summary.mandatory += 1;
+                summary.suggestions = summary.suggestions.saturating_sub(1);
+            }
+            DefectLevel::Suggestions => {
+                summary.suggestions += 1;
+                summary.mandatory = summary.mandatory.saturating_sub(1);
+            }
+        }
+        item.original_defect_level.get_or_insert(item.defect_level);
+        item.defect_level = level;
+    }
+}
+
+/// 移除检查器或问题类型在忽略列表中的问题，返回按检查器统计的移除数量
+pub fn suppress(
+    analysis_result: &mut Vec<AnalysisResultItem>,
+    ignore_analyzers: &[String],
+    ignore_types: &[String],
+) -> BTreeMap<String, usize> {
+    let mut suppressed = BTreeMap::new();
+    analysis_result.retain(|item| {
+        let ignored = ignore_analyzers.contains(&item.analyzer_name)
+            || ignore_types.contains(&item.defect_type);
+        if ignored {
+            *suppressed.entry(item.analyzer_name.clone()).or_insert(0) += 1;
+        }
+        !ignored
+    });
+    suppressed
+}
+
+// 用于对比两次分析结果的问题标识
+fn finding_key(item: &AnalysisResultItem) -> (&str, i32, &str, &str) {
+    (
+        &item.file,
+        item.line,
+        &item.analyzer_name,
+        &item.description,
+    )
+}
+
+/// 与基准版本的分析结果对比，只保留新增的问题，并返回对比统计
+pub fn diff_against_base(
+    analysis_result: &mut Vec<AnalysisResultItem>,
+    base: &AnalysisResult,
+) -> DiffSummary {
+    let base_keys: HashSet<_> = base.cjlint.iter().map(finding_key).collect();
+    let current_keys: HashSet<_> = analysis_result.iter().map(finding_key).collect();
+
+    let removed = base_keys.difference(&current_keys).count();
+    let total = analysis_result.len();
+    analysis_result.retain(|item| !base_keys.contains(&finding_key(item)));
+
+    DiffSummary {
+        base_commit: base.commit.clone(),
+        added: analysis_result.len(),
+        removed,
+        unchanged: total - analysis_result.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        delay_fake_cjlint, fake_cjlint, fake_cjlint_calls, fake_cjlint_library_path,
+        fake_cjlint_overlaps, item, result, set_env,
+    };
+    use vercel_runtime::StatusCode;
+
+    #[test]
+    fn level_filter_keeps_requested_level() {
+        let items = || {
+            vec![
+                item("a.cj", 1, "G.FMT.01", DefectLevel::Suggestions),
+                item("a.cj", 2, "P.ERR.01", DefectLevel::Mandatory),
+            ]
+        };
+        for (level, expected) in [
+            ("all", vec!["G.FMT.01", "P.ERR.01"]),
+            ("mandatory", vec!["P.ERR.01"]),
+            ("suggestions", vec!["G.FMT.01"]),
+        ] {
+            let mut items = items();
+            filter_by_level(&mut items, level.parse().unwrap());
+            let analyzers: Vec<_> = items.iter().map(|item| item.analyzer_name.as_str()).collect();
+            assert_eq!(analyzers, expected, "{}", level);
+        }
+        assert!(matches!(
+            "errors".parse::<LevelFilter>(),
+            Err(RefreshError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn diff_keeps_only_new_findings() {
+        let base = result(vec![
+            item("a.cj", 1, "G.FMT.01", DefectLevel::Suggestions),
+            item("a.cj", 5, "G.NAM.01", DefectLevel::Suggestions),
+        ]);
+        let mut items = vec![
+            item("a.cj", 1, "G.FMT.01", DefectLevel::Suggestions),
+            item("a.cj", 9, "P.ERR.01", DefectLevel::Mandatory),
+        ];
+
+        let summary = diff_against_base(&mut items, &base);
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].analyzer_name, "P.ERR.01");
+        assert_eq!(summary.base_commit, base.commit);
+        assert_eq!((summary.added, summary.removed, summary.unchanged), (1, 1, 1));
+    }
+
+    #[test]
+    fn version_is_taken_from_the_first_version_number() {
+        assert_eq!(parse_version("cjlint version 0.58.3").as_deref(), Some("0.58.3"));
+        assert_eq!(
+            parse_version("Cangjie Lint: v0.53.13, build 2024").as_deref(),
+            Some("0.53.13")
+        );
+        assert_eq!(parse_version("cjlint 1\nunknown"), None);
+    }
+
+    #[test]
+    fn suppression_counts_ignored_findings_per_analyzer() {
+        let findings = || {
+            let mut naming = item("a.cj", 3, "G.NAM.01", DefectLevel::Suggestions);
+            naming.defect_type = "NAMING".to_string();
+            vec![
+                item("a.cj", 1, "G.FMT.01", DefectLevel::Suggestions),
+                item("a.cj", 2, "G.FMT.01", DefectLevel::Suggestions),
+                naming,
+                item("b.cj", 1, "P.ERR.01", DefectLevel::Mandatory),
+            ]
+        };
+        let analyzers = |items: &[AnalysisResultItem]| -> Vec<String> {
+            items.iter().map(|item| item.analyzer_name.clone()).collect()
+        };
+
+        let mut single = findings();
+        let suppressed = suppress(&mut single, &parse_list("G.FMT.01"), &[]);
+        assert_eq!(analyzers(&single), ["G.NAM.01", "P.ERR.01"]);
+        assert_eq!(suppressed, BTreeMap::from([("G.FMT.01".to_string(), 2)]));
+
+        let mut multiple = findings();
+        let suppressed = suppress(&mut multiple, &parse_list(" G.FMT.01, P.ERR.01 ,"), &[]);
+        assert_eq!(analyzers(&multiple), ["G.NAM.01"]);
+        assert_eq!(suppressed.values().sum::<usize>(), 3);
+
+        // 按问题类型忽略时同样按检查器统计
+        let mut by_type = findings();
+        let suppressed = suppress(&mut by_type, &[], &parse_list("NAMING"));
+        assert_eq!(analyzers(&by_type), ["G.FMT.01", "G.FMT.01", "P.ERR.01"]);
+        assert_eq!(suppressed, BTreeMap::from([("G.NAM.01".to_string(), 1)]));
+
+        let mut unknown = findings();
+        let suppressed = suppress(&mut unknown, &parse_list("X.UNKNOWN.01"), &parse_list("NONE"));
+        assert!(suppressed.is_empty());
+        assert_eq!(unknown.len(), 4);
+    }
+
+    #[test]
+    fn cjlintignore_matches_like_gitignore() {
+        let patterns = parse_ignore_file(
+            "# generated code\n\
+             \n\
+             /vendor/\n\
+             build/\n\
+             *.gen.cj\n\
+             src/legacy/*.cj\n\
+             !src/keep.cj\n",
+        );
+        assert_eq!(patterns.len(), 4);
+
+        let mut items: Vec<_> = [
+            "vendor/lib.cj",
+            "src/vendor/lib.cj",
+            "pkg/build/out.cj",
+            "src/api.gen.cj",
+            "src/legacy/old.cj",
+            "src/legacy/deep/old.cj",
+            "src/keep.cj",
+        ]
+        .into_iter()
+        .map(|file| item(file, 1, "G.FMT.01", DefectLevel::Suggestions))
+        .collect();
+
+        assert_eq!(apply_ignore_patterns(&mut items, &patterns), 4);
+        let kept: Vec<_> = items.iter().map(|item| item.file.as_str()).collect();
+        // 锚定的模式只匹配根目录，`*` 不跨越目录
+        assert_eq!(kept, ["src/vendor/lib.cj", "src/legacy/deep/old.cj", "src/keep.cj"]);
+    }
+
+    const REPORT: &str = r#"[{
+        "file": "{dir}/src/main.cj",
+        "line": 1,
+        "column": 1,
+        "endLine": 1,
+        "endColumn": 2,
+        "analyzerName": "G.FMT.01",
+        "description": "demo",
+        "defectLevel": "SUGGESTIONS",
+        "defectType": "FMT",
+        "language": "Cangjie"
+    }]"#;
+
+    #[tokio::test]
+    async fn non_zero_exit_keeps_findings_when_a_report_is_written() {
+        let home = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let home_path = home.path().to_string_lossy().to_string();
+        let root_path = root.path().to_string_lossy().to_string();
+        let _env = set_env(&[("CANGJIE_HOME", Some(home_path.as_str()))]).await;
+
+        fake_cjlint(home.path(), Some(REPORT), 1);
+        let cjlint_run = run_cjlint(root_path.clone(), &[]).await.unwrap();
+        assert_eq!(cjlint_run.items.len(), 1);
+        assert_eq!(cjlint_run.exit_code, 1);
+
+        // 没有报告时仍然视为失败
+        std::fs::remove_file(home.path().join("tools/bin/report.json")).unwrap();
+        fake_cjlint(home.path(), None, 2);
+        let error = run_cjlint(root_path, &[]).await.err().unwrap();
+        assert!(matches!(error, RefreshError::CjlintFailed { code: 2, .. }));
+    }
+
+    #[tokio::test]
+    async fn parallel_lint_merges_packages_within_the_limit() {
+        let home = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        fake_cjlint(home.path(), Some(REPORT), 0);
+        delay_fake_cjlint(home.path(), 0.3);
+        let lint_dirs = vec![root.path().join("a"), root.path().join("b")];
+        let home_path = home.path().to_string_lossy().to_string();
+
+        for (limit, overlapped) in [("1", false), ("2", true)] {
+            let _env = set_env(&[
+                ("CANGJIE_HOME", Some(home_path.as_str())),
+                ("MAX_PARALLEL_LINTS", Some(limit)),
+            ])
+            .await;
+            let cjlint_run = run_cjlint_parallel(lint_dirs.clone(), &[]).await.unwrap();
+
+            // 结果按目录顺序合并
+            let files: Vec<_> = cjlint_run.items.iter().map(|item| item.file.clone()).collect();
+            let expected: Vec<_> = lint_dirs
+                .iter()
+                .map(|dir| format!("{}/src/main.cj", dir.to_string_lossy()))
+                .collect();
+            assert_eq!(files, expected);
+            assert_eq!(cjlint_run.exit_code, 0);
+            assert_eq!(fake_cjlint_overlaps(home.path()).is_empty(), !overlapped);
+        }
+        assert_eq!(fake_cjlint_calls(home.path()).len(), 4);
+    }
+
+    #[test]
+    fn duplicate_findings_are_counted_once() {
+        let mut items = vec![
+            item("a.cj", 3, "G.FMT.01", DefectLevel::Suggestions),
+            item("a.cj", 4, "G.FMT.01", DefectLevel::Suggestions),
+            item("a.cj", 3, "G.FMT.01", DefectLevel::Suggestions),
+            item("a.cj", 3, "G.NAM.01", DefectLevel::Suggestions),
+            item("a.cj", 3, "G.FMT.01", DefectLevel::Suggestions),
+        ];
+
+        assert_eq!(dedup_findings(&mut items), 2);
+        let kept: Vec<_> = items
+            .iter()
+            .map(|item| (item.line, item.analyzer_name.as_str(), item.count))
+            .collect();
+        assert_eq!(
+            kept,
+            [(3, "G.FMT.01", Some(3)), (4, "G.FMT.01", None), (3, "G.NAM.01", None)]
+        );
+    }
+
+    #[test]
+    fn pages_stop_at_the_end_and_offsets_past_it_are_empty() {
+        let page = |limit: Option<usize>, offset: usize| {
+            let mut analysis_result = result(
+                (1..=5)
+                    .map(|line| item("a.cj", line, "G.FMT.01", DefectLevel::Suggestions))
+                    .collect(),
+            );
+            paginate(&mut analysis_result, Pagination { limit, offset });
+            let lines: Vec<i32> = analysis_result.cjlint.iter().map(|item| item.line).collect();
+            let page = analysis_result.page.unwrap();
+            assert_eq!(page.total, 5);
+            (lines, page.next_offset)
+        };
+
+        assert_eq!(page(Some(2), 0), (vec![1, 2], Some(2)));
+        assert_eq!(page(Some(2), 2), (vec![3, 4], Some(4)));
+        // 最后一页恰好取完或不满一页时没有下一页
+        assert_eq!(page(Some(2), 4), (vec![5], None));
+        assert_eq!(page(Some(5), 0), (vec![1, 2, 3, 4, 5], None));
+        assert_eq!(page(None, 3), (vec![4, 5], None));
+        assert_eq!(page(Some(2), 5), (vec![], None));
+        assert_eq!(page(Some(2), 100), (vec![], None));
+        assert_eq!(page(Some(usize::MAX), 1), (vec![2, 3, 4, 5], None));
+    }
+
+    #[test]
+    fn findings_sort_by_file_position_and_analyzer() {
+        let mut items = vec![
+            item("b.cj", 1, "G.FMT.01", DefectLevel::Suggestions),
+            item("a.cj", 10, "G.FMT.01", DefectLevel::Suggestions),
+            item("a.cj", 2, "G.NAM.01", DefectLevel::Suggestions),
+            item("a.cj", 2, "G.ERR.01", DefectLevel::Mandatory),
+        ];
+        items[2].column = 5;
+        items[3].column = 5;
+
+        sort_findings(&mut items);
+        let order: Vec<_> = items
+            .iter()
+            .map(|item| (item.file.as_str(), item.line, item.analyzer_name.as_str()))
+            .collect();
+        // 行号按数值而不是字符串比较
+        assert_eq!(
+            order,
+            [
+                ("a.cj", 2, "G.ERR.01"),
+                ("a.cj", 2, "G.NAM.01"),
+                ("a.cj", 10, "G.FMT.01"),
+                ("b.cj", 1, "G.FMT.01"),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncation_keeps_summary_totals() {
+        let mut analysis_result = result(
+            (1..=5)
+                .map(|line| item("a.cj", line, "G.FMT.01", DefectLevel::Suggestions))
+                .collect(),
+        );
+        analysis_result.summary = summarize(&analysis_result.cjlint);
+
+        truncate_findings(&mut analysis_result, 5);
+        assert!(!analysis_result.truncated);
+        assert_eq!(analysis_result.total_before_truncation, None);
+
+        truncate_findings(&mut analysis_result, 2);
+        assert_eq!(analysis_result.cjlint.len(), 2);
+        assert!(analysis_result.truncated);
+        assert_eq!(analysis_result.total_before_truncation, Some(5));
+        assert_eq!(analysis_result.summary.total, 5);
+    }
+
+    #[tokio::test]
+    async fn requested_max_findings_can_only_lower_the_limit() {
+        let env = set_env(&[("MAX_FINDINGS", Some("10"))]).await;
+        assert_eq!(effective_max_findings(None).unwrap(), 10);
+        assert_eq!(effective_max_findings(Some("5")).unwrap(), 5);
+        assert_eq!(effective_max_findings(Some("50")).unwrap(), 10);
+        for raw in ["0", "-1", "many"] {
+            assert!(matches!(
+                effective_max_findings(Some(raw)),
+                Err(RefreshError::InvalidParameter(_))
+            ));
+        }
+        drop(env);
+
+        // MAX_FINDINGS 为0时不限制，请求仍可以设置上限
+        let _env = set_env(&[("MAX_FINDINGS", Some("0"))]).await;
+        assert_eq!(effective_max_findings(None).unwrap(), 0);
+        assert_eq!(effective_max_findings(Some("50")).unwrap(), 50);
+    }
+
+    #[tokio::test]
+    async fn cjlint_failure_reports_redacted_stderr() {
+        let home = tempfile::tempdir().unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        let repo = tempfile::tempdir().unwrap();
+        fake_cjlint(home.path(), None, 2);
+        let home_path = home.path().to_string_lossy().to_string();
+        let temp_path = temp.path().to_string_lossy().to_string();
+        let repo_path = repo.path().to_string_lossy().to_string();
+        let _env = set_env(&[
+            ("CANGJIE_HOME", Some(home_path.as_str())),
+            ("TEMP_DIR", Some(temp_path.as_str())),
+        ])
+        .await;
+
+        let error = run_cjlint(repo_path.clone(), &[]).await.err().unwrap();
+        let RefreshError::CjlintFailed { code, output } = error else {
+            panic!("unexpected error: {:?}", error);
+        };
+        assert_eq!(code, 2);
+        assert_eq!(
+            output,
+            "STDERR:\nfake cjlint stderr: cannot read <repo>/cjpm.toml, \
+             writing <output>/output.json"
+        );
+        assert!(!output.contains(&repo_path) && !output.contains(&temp_path));
+    }
+
+    #[test]
+    fn diagnostic_output_keeps_the_tail() {
+        assert_eq!(diagnostic_output("only stdout\n", " \n", &[]), "STDOUT:\nonly stdout");
+
+        let stderr = format!("{}\nlast line", "x".repeat(2 * MAX_DIAGNOSTIC_BYTES));
+        let output = diagnostic_output("", &stderr, &[]);
+        assert!(output.starts_with("STDERR (truncated):\n..."));
+        assert!(output.ends_with("\nlast line"));
+        assert!(output.len() < MAX_DIAGNOSTIC_BYTES + 32);
+    }
+
+    #[test]
+    fn language_filter_ignores_case_and_whitespace() {
+        let mut other = item("build.sh", 1, "SH.01", DefectLevel::Suggestions);
+        other.language = "Shell".to_string();
+        let mut items = vec![item("a.cj", 1, "G.FMT.01", DefectLevel::Suggestions), other];
+
+        filter_by_language(&mut items, " cangjie ");
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].file, "a.cj");
+    }
+
+    #[test]
+    fn cjlint_output_accepts_both_known_shapes() {
+        // 未知字段被忽略，缺失的可选字段使用默认值
+        let finding = r#"{
+            "file": "src/main.cj",
+            "line": 3,
+            "analyzerName": "G.FMT.01",
+            "defectLevel": "MANDATORY",
+            "fixSuggestion": "unknown field"
+        }"#;
+        let bare = format!("[{}]", finding);
+        let wrapped = |field: &str| format!(r#"{{"version": "1.0", "{}": {}}}"#, field, bare);
+
+        for content in [bare.clone(), wrapped("results"), wrapped("defects"), wrapped("issues")] {
+            let items = parse_cjlint_output(&content).unwrap();
+            assert_eq!(items.len(), 1, "{}", content);
+            assert_eq!(items[0].analyzer_name, "G.FMT.01");
+            assert_eq!(items[0].defect_level, DefectLevel::Mandatory);
+            assert_eq!((items[0].column, items[0].end_line), (0, 0));
+            assert!(items[0].description.is_empty());
+        }
+        assert!(parse_cjlint_output(r#"{"results": []}"#).unwrap().is_empty());
+    }
+
+    #[test]
+    fn unexpected_cjlint_output_names_the_shape() {
+        let message = |content: &str| {
+            let error = parse_cjlint_output(content).err().unwrap();
+            assert_eq!(error.status_code(), StatusCode::BAD_GATEWAY);
+            error.to_string()
+        };
+
+        assert!(message(r#"{"version": "1.0", "count": 2}"#)
+            .contains("got an object with keys [count, version]"));
+        assert!(message("42").contains("got a number"));
+        assert!(message("not json").contains("not valid JSON"));
+        assert!(message(r#"[{"file": "src/main.cj"}]"#).contains("finding 0:"));
+    }
+
+    #[test]
+    fn severity_overrides_update_levels_and_summary() {
+        let overrides =
+            parse_severity_overrides(" G.FMT.01:MANDATORY, P.ERR.01:suggestions ").unwrap();
+        let mut analysis_result = result(vec![
+            item("a.cj", 1, "G.FMT.01", DefectLevel::Suggestions),
+            item("a.cj", 2, "P.ERR.01", DefectLevel::Mandatory),
+            item("a.cj", 3, "P.ERR.02", DefectLevel::Mandatory),
+        ]);
+        analysis_result.summary = summarize(&analysis_result.cjlint);
+
+        apply_severity_overrides(&mut analysis_result, &overrides);
+        let levels: Vec<_> = analysis_result
+            .cjlint
+            .iter()
+            .map(|item| (item.defect_level, item.original_defect_level))
+            .collect();
+        assert_eq!(
+            levels,
+            [
+                (DefectLevel::Mandatory, Some(DefectLevel::Suggestions)),
+                (DefectLevel::Suggestions, Some(DefectLevel::Mandatory)),
+                (DefectLevel::Mandatory, None),
+            ]
+        );
+        assert_eq!(analysis_result.summary.mandatory, 2);
+        assert_eq!(analysis_result.summary.suggestions, 1);
+
+        for raw in ["G.FMT.01", "G.FMT.01:error"] {
+            assert!(matches!(
+                parse_severity_overrides(raw),
+                Err(RefreshError::InvalidParameter(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn cjlint_gets_the_composed_library_path() {
+        let home = tempfile::tempdir().unwrap();
+        let repo = tempfile::tempdir().unwrap();
+        fake_cjlint(home.path(), Some("[]"), 0);
+        let home_path = home.path().to_string_lossy().to_string();
+        let _env = set_env(&[
+            ("CANGJIE_HOME", Some(home_path.as_str())),
+            ("CJLINT_LIBRARY_PATH", Some("/opt/ssl/lib::/opt/extra/lib")),
+            ("LD_LIBRARY_PATH", Some("/usr/local/lib:")),
+        ])
+        .await;
+
+        run_cjlint(repo.path().to_string_lossy().to_string(), &[]).await.unwrap();
+        assert_eq!(
+            fake_cjlint_library_path(home.path()),
+            format!(
+                "{home}:{home}/runtime/lib/linux_x86_64_llvm:/opt/ssl/lib:/opt/extra/lib:\
+                 /usr/local/lib",
+                home = home_path
+            )
+        );
+    }
+
+    #[test]
+    fn severity_order_survives_pagination_and_truncation() {
+        let mut analysis_result = result(vec![
+            item("a.cj", 1, "G.FMT.01", DefectLevel::Suggestions),
+            item("a.cj", 2, "P.ERR.01", DefectLevel::Mandatory),
+            item("b.cj", 1, "G.NAM.01", DefectLevel::Suggestions),
+            item("b.cj", 5, "P.ERR.02", DefectLevel::Mandatory),
+        ]);
+
+        sort_by_order(&mut analysis_result.cjlint, SortOrder::Severity);
+        paginate(&mut analysis_result, Pagination { limit: Some(3), offset: 0 });
+        truncate_findings(&mut analysis_result, 2);
+
+        let analyzers: Vec<_> =
+            analysis_result.cjlint.iter().map(|item| item.analyzer_name.as_str()).collect();
+        assert_eq!(analyzers, ["P.ERR.01", "P.ERR.02"]);
+        assert_eq!(analysis_result.page.unwrap().next_offset, Some(3));
+        assert_eq!(analysis_result.total_before_truncation, Some(3));
+    }
+
+    #[test]
+    fn top_issues_are_ordered_by_count_then_name() {
+        let mut findings = Vec::new();
+        let mut add = |analyzer: &str, defect_type: &str, count: usize| {
+            for _ in 0..count {
+                let mut finding = item("a.cj", 1, analyzer, DefectLevel::Suggestions);
+                finding.defect_type = defect_type.to_string();
+                findings.push(finding);
+            }
+        };
+        add("G.NAM.01", "NAM", 3);
+        add("G.FMT.01", "FMT", 3);
+        add("G.FMT.01", "STYLE", 2);
+        add("G.ERR.01", "ERR", 2);
+        for i in (1..=9).rev() {
+            add(&format!("S.0{}", i), "X", 1);
+        }
+
+        let summary = summarize(&findings);
+        let top: Vec<(&str, &str, usize)> = summary
+            .top_issues
+            .iter()
+            .map(|issue| (issue.analyzer.as_str(), issue.defect_type.as_str(), issue.count))
+            .collect();
+        assert_eq!(
+            top,
+            [
+                ("G.FMT.01", "FMT", 3),
+                ("G.NAM.01", "NAM", 3),
+                ("G.ERR.01", "ERR", 2),
+                ("G.FMT.01", "STYLE", 2),
+                ("S.01", "X", 1),
+                ("S.02", "X", 1),
+                ("S.03", "X", 1),
+                ("S.04", "X", 1),
+                ("S.05", "X", 1),
+                ("S.06", "X", 1),
+            ]
+        );
+        assert_eq!(summary.top_issues.len(), TOP_ISSUES_LIMIT);
+        let by_type: Vec<_> = summary.by_type.iter().map(|(t, &n)| (t.as_str(), n)).collect();
+        assert_eq!(by_type, [("ERR", 2), ("FMT", 3), ("NAM", 3), ("STYLE", 2), ("X", 9)]);
+        assert_eq!(summary.by_analyzer["G.FMT.01"], 5);
+    }
+
+    #[test]
+    fn windows_separators_are_normalized() {
+        let items = vec![
+            item("C:\\work\\repo\\src\\main.cj", 1, "G.FMT.01", DefectLevel::Suggestions),
+            item("C:/work/repo/src/lib.cj", 1, "G.FMT.01", DefectLevel::Suggestions),
+            item("D:\\other\\util.cj", 1, "G.FMT.01", DefectLevel::Suggestions),
+        ];
+
+        let items = process_analysis_result(items, "C:\\work\\repo\\");
+        let files: Vec<_> = items
+            .iter()
+            .map(|item| (item.file.as_str(), item.absolute_path.as_deref()))
+            .collect();
+        assert_eq!(
+            files,
+            [
+                ("src/main.cj", Some("C:\\work\\repo\\src\\main.cj")),
+                ("src/lib.cj", Some("C:/work/repo/src/lib.cj")),
+                ("D:/other/util.cj", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn path_filters_include_then_exclude() {
+        let mut items = vec![
+            item("src/main.cj", 1, "G.FMT.01", DefectLevel::Suggestions),
+            item("src/gen/api.cj", 1, "G.FMT.01", DefectLevel::Suggestions),
+            item("src/gen/api.cj", 2, "G.FMT.01", DefectLevel::Suggestions),
+            item("tests/main_test.cj", 1, "G.FMT.01", DefectLevel::Suggestions),
+        ];
+        let include = parse_path_patterns("/src/", "include_paths").unwrap();
+        let exclude = parse_path_patterns("gen/, *_test.cj", "exclude_paths").unwrap();
+
+        assert_eq!(filter_by_paths(&mut items, &include, &exclude), (2, 3));
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].file, "src/main.cj");
+
+        assert!(matches!(
+            parse_path_patterns("src/[", "include_paths"),
+            Err(RefreshError::InvalidParameter(message)) if message.contains("include_paths")
+        ));
+    }
+
+    #[test]
+    fn changed_only_keeps_findings_in_changed_files() {
+        let changed: BTreeSet<String> = ["src/new.cj".to_string()].into_iter().collect();
+        let mut items = vec![
+            item("src/new.cj", 1, "G.FMT.01", DefectLevel::Suggestions),
+            item("src/old.cj", 1, "G.FMT.01", DefectLevel::Suggestions),
+            item("src/new.cj", 2, "G.FMT.01", DefectLevel::Suggestions),
+        ];
+
+        assert_eq!(filter_to_changed(&mut items, &changed), 1);
+        assert!(items.iter().all(|item| item.file == "src/new.cj"));
+    }
+}
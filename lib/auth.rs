@@ -0,0 +1,24 @@
+use crate::config::admin_secret;
+use crate::error::RefreshError;
+use vercel_runtime::Request;
+
+/// 校验X-Admin-Secret请求头，未配置ADMIN_SECRET时拒绝所有请求
+pub fn authorize_admin(req: &Request) -> Result<(), RefreshError> {
+    let expected = admin_secret().ok_or(RefreshError::Unauthorized)?;
+    let provided = req
+        .headers()
+        .get("X-Admin-Secret")
+        .and_then(|value| value.to_str().ok())
+        .ok_or(RefreshError::Unauthorized)?;
+
+    if constant_time_eq(provided.trim().as_bytes(), expected.as_bytes()) {
+        Ok(())
+    } else {
+        Err(RefreshError::Unauthorized)
+    }
+}
+
+/// 比较耗时与内容无关，避免通过响应时间猜测密钥
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,64 @@
+// 强制问题数量不超过该值时显示为黄色，超过时显示为红色
+const WARNING_MANDATORY_THRESHOLD: usize = 10;
+
+const COLOR_GREEN: &str = "#4c1";
+const COLOR_YELLOW: &str = "#dfb317";
+const COLOR_RED: &str = "#e05d44";
+const COLOR_GRAY: &str = "#9f9f9f";
+
+// 估算的平均字符宽度和两侧留白，用于计算徽章宽度
+const CHAR_WIDTH: usize = 7;
+const PADDING: usize = 10;
+
+/// 根据强制问题数量选择徽章颜色：没有强制问题为绿色，少量为黄色，较多为红色
+pub fn badge_color(mandatory: usize) -> &'static str {
+    match mandatory {
+        0 => COLOR_GREEN,
+        n if n <= WARNING_MANDATORY_THRESHOLD => COLOR_YELLOW,
+        _ => COLOR_RED,
+    }
+}
+
+/// 生成显示问题总数的徽章，没有分析结果时传入 None 生成灰色的 unknown 徽章
+pub fn render_badge(counts: Option<(usize, usize)>) -> String {
+    let (message, color) = match counts {
+        Some((total, mandatory)) => {
+            let unit = if total == 1 { "issue" } else { "issues" };
+            (format!("{} {}", total, unit), badge_color(mandatory))
+        }
+        None => ("unknown".to_string(), COLOR_GRAY),
+    };
+    render("cjlint", &message, color)
+}
+
+/// 生成 shields 风格的两段式徽章
+fn render(label: &str, message: &str, color: &str) -> String {
+    let label_width = label.chars().count() * CHAR_WIDTH + PADDING;
+    let message_width = message.chars().count() * CHAR_WIDTH + PADDING;
+    let width = label_width + message_width;
+    let label_x = label_width / 2;
+    let message_x = label_width + message_width / 2;
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20"
+     role="img" aria-label="{label}: {message}">
+  <title>{label}: {message}</title>
+  <linearGradient id="s" x2="0" y2="100%">
+    <stop offset="0" stop-color="#bbb" stop-opacity=".1"/>
+    <stop offset="1" stop-opacity=".1"/>
+  </linearGradient>
+  <clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath>
+  <g clip-path="url(#r)">
+    <rect width="{label_width}" height="20" fill="#555"/>
+    <rect x="{label_width}" width="{message_width}" height="20" fill="{color}"/>
+    <rect width="{width}" height="20" fill="url(#s)"/>
+  </g>
+  <g fill="#fff" text-anchor="middle" font-size="11"
+     font-family="Verdana,Geneva,DejaVu Sans,sans-serif">
+    <text x="{label_x}" y="14">{label}</text>
+    <text x="{message_x}" y="14">{message}</text>
+  </g>
+</svg>
+"##
+    )
+}
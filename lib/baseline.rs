@@ -0,0 +1,48 @@
+use crate::models::AnalysisResultItem;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+
+/// 基线中问题的指纹，不包含行号和列号，代码上下移动后指纹保持不变
+pub fn baseline_fingerprint(item: &AnalysisResultItem) -> String {
+    let mut hasher = Sha256::new();
+    for part in [&item.file, &item.analyzer_name, &item.description] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// 移除指纹在基线中的问题，返回移除的数量
+pub fn apply_baseline(
+    analysis_result: &mut Vec<AnalysisResultItem>,
+    baseline: &HashSet<String>,
+) -> usize {
+    let total = analysis_result.len();
+    analysis_result.retain(|item| !baseline.contains(&baseline_fingerprint(item)));
+    total - analysis_result.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DefectLevel;
+    use crate::test_support::item;
+
+    #[test]
+    fn baseline_suppresses_known_findings_after_lines_move() {
+        let known = item("src/main.cj", 3, "G.FMT.01", DefectLevel::Suggestions);
+        let baseline: HashSet<String> = [baseline_fingerprint(&known)].into_iter().collect();
+
+        let mut items = vec![
+            item("src/main.cj", 8, "G.FMT.01", DefectLevel::Suggestions),
+            item("src/main.cj", 3, "G.NAM.01", DefectLevel::Suggestions),
+            item("src/lib.cj", 3, "G.FMT.01", DefectLevel::Suggestions),
+        ];
+        assert_eq!(apply_baseline(&mut items, &baseline), 1);
+        let kept: Vec<_> = items
+            .iter()
+            .map(|item| (item.file.as_str(), item.analyzer_name.as_str()))
+            .collect();
+        assert_eq!(kept, [("src/main.cj", "G.NAM.01"), ("src/lib.cj", "G.FMT.01")]);
+    }
+}
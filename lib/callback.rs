@@ -0,0 +1,164 @@
+use std::time::Duration;
+use serde::Serialize;
+use tracing::{info, warn};
+use url::{Host, Url};
+use crate::config::{callback_allowed_hosts, callback_timeout_seconds};
+use crate::error::RefreshError;
+use crate::models::{AnalysisResult, AnalysisSummary};
+use crate::utils::sanitize_repo_url;
+
+// 回调中只发送统计信息，完整结果可以通过 cached 接口获取
+#[derive(Debug, Serialize)]
+pub struct CallbackPayload<'a> {
+    pub repo: &'a str,
+    pub commit: &'a str,
+    pub created_at: i64,
+    pub summary: &'a AnalysisSummary,
+}
+
+impl<'a> CallbackPayload<'a> {
+    pub fn new(repo: &'a str, analysis_result: &'a AnalysisResult) -> Self {
+        Self {
+            repo,
+            commit: &analysis_result.commit,
+            created_at: analysis_result.created_at,
+            summary: &analysis_result.summary,
+        }
+    }
+}
+
+/// 校验回调地址，只允许 CALLBACK_ALLOWED_HOSTS 中的 https 地址，防止借回调访问内网
+pub fn validate_callback_url(callback_url: &str) -> Result<Url, RefreshError> {
+    let invalid = |reason: String| {
+        RefreshError::InvalidParameter(format!("Invalid callback_url: {}", reason))
+    };
+    let url = Url::parse(callback_url).map_err(|e| invalid(e.to_string()))?;
+
+    if url.scheme() != "https" {
+        return Err(invalid(format!("scheme {} is not allowed", url.scheme())));
+    }
+
+    let host = match url.host() {
+        Some(Host::Domain(domain)) => domain.to_lowercase(),
+        Some(Host::Ipv4(_)) | Some(Host::Ipv6(_)) => {
+            return Err(invalid("IP addresses are not allowed".to_string()));
+        }
+        None => return Err(invalid("missing host".to_string())),
+    };
+
+    if !callback_allowed_hosts().contains(&host) {
+        return Err(invalid(format!("host {} is not allowed", host)));
+    }
+
+    Ok(url)
+}
+
+/// 向回调地址POST分析统计，失败只记录日志，不影响请求本身的结果
+pub async fn notify_callback(callback_url: &Url, payload: &CallbackPayload<'_>) {
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(callback_timeout_seconds()))
+        // 重定向可能把请求带到白名单以外的地址
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("Failed to build callback client: {}", e);
+            return;
+        }
+    };
+
+    let target = sanitize_repo_url(callback_url.as_str());
+    match client.post(callback_url.clone()).json(payload).send().await {
+        Ok(response) if response.status().is_success() => {
+            info!(callback = %target, "Callback delivered");
+        }
+        Ok(response) => {
+            warn!(callback = %target, status = %response.status(), "Callback rejected");
+        }
+        Err(e) => {
+            warn!(callback = %target, "Callback failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DefectLevel;
+    use crate::test_support::{item, result, set_env};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    /// 接收一次POST请求的回调服务器，返回地址和请求体
+    fn callback_server(status: &'static str) -> (Url, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/hook", listener.local_addr().unwrap())).unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status);
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+            String::from_utf8(body).unwrap()
+        });
+        (url, server)
+    }
+
+    #[tokio::test]
+    async fn callback_posts_the_summary() {
+        let mut analysis_result =
+            result(vec![item("src/main.cj", 3, "G.FMT.01", DefectLevel::Suggestions)]);
+        analysis_result.commit = "abc123".to_string();
+        analysis_result.summary.total = 1;
+        let repo = "https://github.com/demo/callback";
+
+        for status in ["200 OK", "500 Internal Server Error"] {
+            let (url, server) = callback_server(status);
+            // 回调失败只记录日志
+            notify_callback(&url, &CallbackPayload::new(repo, &analysis_result)).await;
+
+            let payload: serde_json::Value = serde_json::from_str(&server.join().unwrap()).unwrap();
+            assert_eq!(payload["repo"], repo);
+            assert_eq!(payload["commit"], "abc123");
+            assert_eq!(payload["summary"]["total"], 1);
+            assert!(payload.get("cjlint").is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn callback_url_must_be_an_allowed_https_host() {
+        let _env = set_env(&[("CALLBACK_ALLOWED_HOSTS", Some("hooks.example.com"))]).await;
+
+        assert!(validate_callback_url("https://hooks.example.com/cjlint").is_ok());
+        assert!(validate_callback_url("https://HOOKS.example.com/cjlint").is_ok());
+        for url in [
+            "http://hooks.example.com/cjlint",
+            "https://other.example.com/cjlint",
+            "https://127.0.0.1/cjlint",
+            "https://[::1]/cjlint",
+            "not a url",
+        ] {
+            assert!(
+                matches!(validate_callback_url(url), Err(RefreshError::InvalidParameter(_))),
+                "{}",
+                url
+            );
+        }
+    }
+}
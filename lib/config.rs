@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::str::FromStr;
+use sysinfo::{MemoryRefreshKind, System};
+use tracing::warn;
+use crate::models::DefectLevel;
+
+// 分析结果默认缓存7天
+const DEFAULT_CACHE_TTL_SECONDS: u64 = 7 * 24 * 60 * 60;
+const DEFAULT_CJLINT_TIMEOUT_SECONDS: u64 = 120;
+// 略小于Vercel函数的最长执行时间，留出返回响应的时间
+const DEFAULT_REQUEST_TIMEOUT_SECONDS: u64 = 280;
+const DEFAULT_ALLOWED_REPO_HOSTS: &str = "github.com,gitcode.com,gitee.com";
+// 每个并发克隆预留的内存，用于根据可用内存计算默认的并发克隆数
+const MEMORY_PER_CLONE_BYTES: u64 = 512 * 1024 * 1024;
+const DEFAULT_CLONE_RETRY_ATTEMPTS: u32 = 3;
+const DEFAULT_CLONE_SLOT_TIMEOUT_SECONDS: u64 = 5;
+const DEFAULT_TEMP_DIR: &str = "/tmp";
+const DEFAULT_CANGJIE_HOME: &str = "/tmp/cj";
+const DEFAULT_HISTORY_LIMIT: usize = 30;
+const DEFAULT_MAX_CLONE_DEPTH: u32 = 1000;
+const DEFAULT_MAX_PARALLEL_LINTS: usize = 2;
+const DEFAULT_SUBMODULE_MAX_DEPTH: usize = 2;
+const DEFAULT_SUBMODULE_MAX_COUNT: usize = 20;
+const DEFAULT_MAX_REPO_SIZE_MB: u64 = 500;
+const DEFAULT_RATE_LIMIT_MAX_REQUESTS: u64 = 10;
+const DEFAULT_RATE_LIMIT_WINDOW_SECONDS: u64 = 60;
+const DEFAULT_MAX_FINDINGS: usize = 10000;
+const DEFAULT_CALLBACK_TIMEOUT_SECONDS: u64 = 5;
+const DEFAULT_BATCH_MAX_REPOS: usize = 10;
+const DEFAULT_BATCH_CONCURRENCY: usize = 2;
+const DEFAULT_MAX_RETAINED_REPOS: usize = 3;
+// 保留的克隆目录默认1小时后自动清理
+const DEFAULT_RETAINED_REPO_TTL_SECONDS: u64 = 60 * 60;
+
+/// 读取环境变量并解析，未设置或无法解析时使用默认值
+fn env_or<T: FromStr>(name: &str, default: T) -> T {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+/// 读取布尔类型的环境变量，"1" 或 "true" 视为开启
+fn env_flag(name: &str) -> bool {
+    env::var(name)
+        .map(|value| matches!(value.trim().to_lowercase().as_str(), "1" | "true"))
+        .unwrap_or(false)
+}
+
+/// 分析结果在Redis中的过期时间（秒）
+pub fn cache_ttl_seconds() -> u64 {
+    env_or("CACHE_TTL_SECONDS", DEFAULT_CACHE_TTL_SECONDS)
+}
+
+/// 克隆目录和cjlint输出文件所在的临时目录，Vercel 只允许写入 /tmp
+pub fn temp_dir() -> String {
+    env::var("TEMP_DIR").unwrap_or_else(|_| DEFAULT_TEMP_DIR.to_string())
+}
+
+/// cjlint工具链的解压目录，同时作为运行时的 `CANGJIE_HOME`，默认 /tmp/cj
+pub fn cangjie_home() -> PathBuf {
+    env::var("CANGJIE_HOME")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CANGJIE_HOME))
+}
+
+/// 解压后的cjlint可执行文件路径
+pub fn cjlint_bin() -> PathBuf {
+    cangjie_home().join("tools/bin/cjlint")
+}
+
+/// cjlint额外需要的共享库目录，格式与 `LD_LIBRARY_PATH` 相同
+pub fn cjlint_library_paths() -> Vec<PathBuf> {
+    env::var_os("CJLINT_LIBRARY_PATH")
+        .map(|raw| env::split_paths(&raw).filter(|path| !path.as_os_str().is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// 进程内同时进行的克隆数量上限，默认按每个克隆512MB根据总内存计算
+pub fn max_concurrent_clones() -> usize {
+    let default = {
+        let mut system = System::new();
+        system.refresh_memory_specifics(MemoryRefreshKind::nothing().with_ram());
+        (system.total_memory() / MEMORY_PER_CLONE_BYTES).max(1) as usize
+    };
+    env_or("MAX_CONCURRENT_CLONES", default).max(1)
+}
+
+/// 克隆遇到暂时性错误时的最大尝试次数，包含第一次
+pub fn clone_retry_attempts() -> u32 {
+    env_or("CLONE_RETRY_ATTEMPTS", DEFAULT_CLONE_RETRY_ATTEMPTS).clamp(1, 10)
+}
+
+/// 等待克隆名额的最长时间（秒）
+pub fn clone_slot_timeout_seconds() -> u64 {
+    env_or("CLONE_SLOT_TIMEOUT_SECONDS", DEFAULT_CLONE_SLOT_TIMEOUT_SECONDS)
+}
+
+/// cjlint单次运行的最长时间（秒）
+pub fn cjlint_timeout_seconds() -> u64 {
+    env_or("CJLINT_TIMEOUT_SECONDS", DEFAULT_CJLINT_TIMEOUT_SECONDS)
+}
+
+/// 整个刷新请求的最长处理时间（秒）
+pub fn request_timeout_seconds() -> u64 {
+    env_or("REQUEST_TIMEOUT_SECONDS", DEFAULT_REQUEST_TIMEOUT_SECONDS).max(1)
+}
+
+/// 允许克隆的仓库域名，逗号分隔
+pub fn allowed_repo_hosts() -> Vec<String> {
+    env::var("ALLOWED_REPO_HOSTS")
+        .unwrap_or_else(|_| DEFAULT_ALLOWED_REPO_HOSTS.to_string())
+        .split(',')
+        .map(|host| host.trim().to_lowercase())
+        .filter(|host| !host.is_empty())
+        .collect()
+}
+
+/// 克隆时使用的镜像域名，REPO_MIRRORS 为原域名到镜像域名的JSON对象，
+/// 如 `{"github.com": "github.example.com"}`，格式错误时不使用镜像
+pub fn repo_mirrors() -> HashMap<String, String> {
+    let Ok(raw) = env::var("REPO_MIRRORS") else {
+        return HashMap::new();
+    };
+
+    match serde_json::from_str::<HashMap<String, String>>(&raw) {
+        Ok(mirrors) => mirrors
+            .into_iter()
+            .map(|(host, mirror)| (host.trim().to_lowercase(), mirror.trim().to_string()))
+            .filter(|(host, mirror)| !host.is_empty() && !mirror.is_empty())
+            .collect(),
+        Err(e) => {
+            warn!("Ignoring invalid REPO_MIRRORS: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+/// 强制修改检查器问题级别的配置，格式为 `{"检查器名": "MANDATORY" | "SUGGESTIONS"}`
+pub fn severity_overrides() -> HashMap<String, DefectLevel> {
+    let Ok(raw) = env::var("SEVERITY_OVERRIDES") else {
+        return HashMap::new();
+    };
+
+    serde_json::from_str(&raw).unwrap_or_else(|e| {
+        warn!("Ignoring invalid SEVERITY_OVERRIDES: {}", e);
+        HashMap::new()
+    })
+}
+
+/// 是否允许通过 local_path 参数分析服务器上的本地目录，默认关闭
+pub fn allow_local_paths() -> bool {
+    env_flag("ALLOW_LOCAL_PATHS")
+}
+
+/// 允许通过 local_path 分析的目录，只有该目录及其子目录可以被分析，未设置时拒绝所有本地目录
+pub fn local_path_root() -> Option<PathBuf> {
+    env::var("LOCAL_PATH_ROOT")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| PathBuf::from(value.trim()))
+}
+
+/// Redis不可用时是否仍返回分析结果，默认关闭，此时任何Redis错误都会使请求失败
+pub fn redis_optional() -> bool {
+    env_flag("REDIS_OPTIONAL")
+}
+
+/// 是否允许管理员通过 keep_repo 参数保留克隆目录用于调试，默认关闭
+pub fn allow_keep_repo() -> bool {
+    env_flag("ALLOW_KEEP_REPO")
+}
+
+/// 是否在返回 202 后于后台执行异步刷新，只适用于常驻进程的部署，默认关闭
+///
+/// Vercel 在响应返回后冻结实例，后台任务不会继续执行，因此默认在返回响应前执行完任务
+pub fn background_jobs() -> bool {
+    env_flag("BACKGROUND_JOBS")
+}
+
+/// 同时保留的克隆目录数量上限，避免占满磁盘
+pub fn max_retained_repos() -> usize {
+    env_or("MAX_RETAINED_REPOS", DEFAULT_MAX_RETAINED_REPOS)
+}
+
+/// 保留的克隆目录在多少秒后被自动清理
+///
+/// 清理接口可能运行在另一个实例上看不到这些目录，因此保留的目录同样需要过期
+pub fn retained_repo_ttl_seconds() -> u64 {
+    env_or("RETAINED_REPO_TTL_SECONDS", DEFAULT_RETAINED_REPO_TTL_SECONDS)
+}
+
+/// 是否禁用按提交缓存的快速返回，便于调试时强制重新分析
+pub fn cache_disabled() -> bool {
+    env_flag("DISABLE_CACHE")
+}
+
+/// 追加到每次cjlint调用的额外参数，以空白分隔
+pub fn cjlint_extra_args() -> String {
+    env::var("CJLINT_EXTRA_ARGS").unwrap_or_default()
+}
+
+/// 每个仓库保留的分析历史条数
+pub fn history_limit() -> usize {
+    env_or("HISTORY_LIMIT", DEFAULT_HISTORY_LIMIT).max(1)
+}
+
+/// 请求中指定的抓取深度上限，不影响完整历史的克隆
+pub fn max_clone_depth() -> u32 {
+    env_or("MAX_CLONE_DEPTH", DEFAULT_MAX_CLONE_DEPTH).max(1)
+}
+
+/// 同时运行的cjlint进程数量上限，至少为1
+pub fn max_parallel_lints() -> usize {
+    env_or("MAX_PARALLEL_LINTS", DEFAULT_MAX_PARALLEL_LINTS).max(1)
+}
+
+/// 子模块的最大嵌套深度，仓库直接引用的子模块深度为1
+pub fn submodule_max_depth() -> usize {
+    env_or("SUBMODULE_MAX_DEPTH", DEFAULT_SUBMODULE_MAX_DEPTH)
+}
+
+/// 单次克隆最多更新的子模块数量
+pub fn submodule_max_count() -> usize {
+    env_or("SUBMODULE_MAX_COUNT", DEFAULT_SUBMODULE_MAX_COUNT)
+}
+
+/// 克隆时允许下载的数据量以及克隆目录（含子模块）允许占用的最大空间（字节）
+///
+/// 优先读取 MAX_REPO_BYTES，未设置时读取以MB为单位的 MAX_REPO_SIZE_MB
+pub fn max_repo_size_bytes() -> u64 {
+    env_or(
+        "MAX_REPO_BYTES",
+        env_or("MAX_REPO_SIZE_MB", DEFAULT_MAX_REPO_SIZE_MB).saturating_mul(1024 * 1024),
+    )
+}
+
+/// 单次响应中最多返回的问题数量，设置为0时不限制
+pub fn max_findings() -> usize {
+    env_or("MAX_FINDINGS", DEFAULT_MAX_FINDINGS)
+}
+
+/// 每个时间窗口内允许的刷新次数，设置为0时关闭限流
+pub fn rate_limit_max_requests() -> u64 {
+    env_or("RATE_LIMIT_MAX_REQUESTS", DEFAULT_RATE_LIMIT_MAX_REQUESTS)
+}
+
+/// 限流的时间窗口（秒）
+pub fn rate_limit_window_seconds() -> u64 {
+    env_or("RATE_LIMIT_WINDOW_SECONDS", DEFAULT_RATE_LIMIT_WINDOW_SECONDS).max(1)
+}
+
+/// 是否同时按客户端IP限流
+pub fn rate_limit_per_ip() -> bool {
+    env_flag("RATE_LIMIT_PER_IP")
+}
+
+/// 允许跨域访问的来源，逗号分隔，"*" 表示允许任意来源，未设置时不返回CORS响应头
+pub fn cors_allowed_origins() -> Vec<String> {
+    env::var("CORS_ALLOWED_ORIGINS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|origin| origin.trim().trim_end_matches('/').to_string())
+        .filter(|origin| !origin.is_empty())
+        .collect()
+}
+
+/// 管理接口使用的共享密钥，未设置时管理接口不可用
+pub fn admin_secret() -> Option<String> {
+    env::var("ADMIN_SECRET")
+        .ok()
+        .map(|secret| secret.trim().to_string())
+        .filter(|secret| !secret.is_empty())
+}
+
+/// 允许作为完成回调地址的域名，未设置时不接受 callback_url
+pub fn callback_allowed_hosts() -> Vec<String> {
+    env::var("CALLBACK_ALLOWED_HOSTS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|host| host.trim().to_lowercase())
+        .filter(|host| !host.is_empty())
+        .collect()
+}
+
+/// 发送完成回调的超时时间（秒）
+pub fn callback_timeout_seconds() -> u64 {
+    env_or("CALLBACK_TIMEOUT_SECONDS", DEFAULT_CALLBACK_TIMEOUT_SECONDS).max(1)
+}
+
+/// 一次批量请求中允许的仓库数量上限
+pub fn batch_max_repos() -> usize {
+    env_or("BATCH_MAX_REPOS", DEFAULT_BATCH_MAX_REPOS).max(1)
+}
+
+/// 批量请求中同时分析的仓库数量，至少为1
+pub fn batch_concurrency() -> usize {
+    env_or("BATCH_CONCURRENCY", DEFAULT_BATCH_CONCURRENCY).max(1)
+}
@@ -0,0 +1,193 @@
+use std::fmt;
+use vercel_runtime::StatusCode;
+
+/// 刷新分析过程中可能出现的错误
+///
+/// 实现了 `std::error::Error`，因此可以直接通过 `?` 或 `Error::from` 转换为 `vercel_runtime::Error`
+#[derive(Debug)]
+pub enum RefreshError {
+    MissingRepoParam,
+    Unauthorized,
+    InvalidParameter(String),
+    InvalidRepoUrl(String),
+    CloneFailed(git2::Error),
+    RepositoryNotFound(git2::Error),
+    RepositoryAuthRequired(git2::Error),
+    RevisionNotFound {
+        revision: String,
+        source: git2::Error,
+    },
+    EmptyRepository,
+    RepositoryTooLarge {
+        limit_bytes: u64,
+    },
+    SubmoduleLimitExceeded(String),
+    NoCjpmToml,
+    InvalidCjpmToml(String),
+    CjlintFailed {
+        code: i32,
+        output: String,
+    },
+    CjlintOutputMissing {
+        source: std::io::Error,
+        output: String,
+    },
+    CjlintOutputInvalid(String),
+    CjlintTimeout {
+        seconds: u64,
+    },
+    RequestTimeout {
+        seconds: u64,
+    },
+    CacheMiss,
+    JobNotFound,
+    RateLimited {
+        retry_after: u64,
+    },
+    ServerBusy {
+        retry_after: u64,
+    },
+    RedisUnavailable(redis::RedisError),
+    Serialization(serde_json::Error),
+    Io(std::io::Error),
+}
+
+impl RefreshError {
+    /// 需要通过 Retry-After 告知调用方的等待时间（秒）
+    pub fn retry_after(&self) -> Option<u64> {
+        match self {
+            RefreshError::RateLimited { retry_after }
+            | RefreshError::ServerBusy { retry_after } => Some(*retry_after),
+            _ => None,
+        }
+    }
+
+    /// 错误对应的HTTP状态码
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            RefreshError::MissingRepoParam
+            | RefreshError::InvalidParameter(_)
+            | RefreshError::InvalidRepoUrl(_) => StatusCode::BAD_REQUEST,
+            RefreshError::Unauthorized | RefreshError::RepositoryAuthRequired(_) => {
+                StatusCode::UNAUTHORIZED
+            }
+            RefreshError::RevisionNotFound { .. }
+            | RefreshError::RepositoryNotFound(_)
+            | RefreshError::CacheMiss
+            | RefreshError::JobNotFound => StatusCode::NOT_FOUND,
+            RefreshError::CloneFailed(_) | RefreshError::CjlintOutputInvalid(_) => {
+                StatusCode::BAD_GATEWAY
+            }
+            RefreshError::RepositoryTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            RefreshError::EmptyRepository
+            | RefreshError::SubmoduleLimitExceeded(_)
+            | RefreshError::NoCjpmToml
+            | RefreshError::InvalidCjpmToml(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            RefreshError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            RefreshError::RedisUnavailable(_) | RefreshError::ServerBusy { .. } => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            RefreshError::CjlintTimeout { .. } | RefreshError::RequestTimeout { .. } => {
+                StatusCode::GATEWAY_TIMEOUT
+            }
+            RefreshError::CjlintFailed { .. }
+            | RefreshError::CjlintOutputMissing { .. }
+            | RefreshError::Serialization(_)
+            | RefreshError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl fmt::Display for RefreshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RefreshError::MissingRepoParam => write!(f, "repo query parameter is required"),
+            RefreshError::Unauthorized => write!(f, "missing or invalid admin secret"),
+            RefreshError::InvalidParameter(message) => write!(f, "{}", message),
+            RefreshError::InvalidRepoUrl(message) => write!(f, "Invalid repo URL: {}", message),
+            RefreshError::CloneFailed(e) => write!(f, "Failed to clone repository: {}", e),
+            RefreshError::RepositoryNotFound(e) => write!(f, "Repository not found: {}", e),
+            RefreshError::RepositoryAuthRequired(e) => {
+                write!(f, "Repository requires authentication: {}", e)
+            }
+            RefreshError::RevisionNotFound { revision, source } => {
+                write!(f, "Failed to resolve revision {}: {}", revision, source)
+            }
+            RefreshError::EmptyRepository => write!(f, "repository has no commits"),
+            RefreshError::RepositoryTooLarge { limit_bytes } => {
+                write!(f, "repository exceeds the size limit of {} bytes", limit_bytes)
+            }
+            RefreshError::SubmoduleLimitExceeded(message) => {
+                write!(f, "Submodule limit exceeded: {}", message)
+            }
+            RefreshError::NoCjpmToml => write!(f, "No cjpm.toml found"),
+            RefreshError::InvalidCjpmToml(message) => write!(f, "Invalid cjpm.toml: {}", message),
+            RefreshError::CjlintFailed { code, output } => {
+                write!(f, "cjlint command failed with exit code: {}\n{}", code, output)
+            }
+            RefreshError::CjlintOutputMissing { source, output } => {
+                write!(f, "Failed to read cjlint output: {}\n{}", source, output)
+            }
+            RefreshError::CjlintOutputInvalid(message) => {
+                write!(f, "Unexpected cjlint report structure: {}", message)
+            }
+            RefreshError::CjlintTimeout { seconds } => {
+                write!(f, "cjlint did not finish within {} seconds", seconds)
+            }
+            RefreshError::RequestTimeout { seconds } => {
+                write!(f, "request did not finish within {} seconds", seconds)
+            }
+            RefreshError::CacheMiss => write!(f, "No cached analysis found for this repository"),
+            RefreshError::JobNotFound => write!(f, "job not found or expired"),
+            RefreshError::RateLimited { retry_after } => {
+                write!(f, "Too many refresh requests, retry after {} seconds", retry_after)
+            }
+            RefreshError::ServerBusy { retry_after } => {
+                write!(f, "Too many analyses in progress, retry after {} seconds", retry_after)
+            }
+            RefreshError::RedisUnavailable(e) => write!(f, "Failed to access Redis: {}", e),
+            RefreshError::Serialization(e) => write!(f, "Serialization failed: {}", e),
+            RefreshError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RefreshError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RefreshError::CloneFailed(e)
+            | RefreshError::RepositoryNotFound(e)
+            | RefreshError::RepositoryAuthRequired(e) => Some(e),
+            RefreshError::RevisionNotFound { source, .. } => Some(source),
+            RefreshError::CjlintOutputMissing { source, .. } => Some(source),
+            RefreshError::RedisUnavailable(e) => Some(e),
+            RefreshError::Serialization(e) => Some(e),
+            RefreshError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<git2::Error> for RefreshError {
+    fn from(e: git2::Error) -> Self {
+        RefreshError::CloneFailed(e)
+    }
+}
+
+impl From<redis::RedisError> for RefreshError {
+    fn from(e: redis::RedisError) -> Self {
+        RefreshError::RedisUnavailable(e)
+    }
+}
+
+impl From<serde_json::Error> for RefreshError {
+    fn from(e: serde_json::Error) -> Self {
+        RefreshError::Serialization(e)
+    }
+}
+
+impl From<std::io::Error> for RefreshError {
+    fn from(e: std::io::Error) -> Self {
+        RefreshError::Io(e)
+    }
+}
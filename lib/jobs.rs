@@ -0,0 +1,211 @@
+use std::time::SystemTime;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use crate::config::request_timeout_seconds;
+use crate::error::RefreshError;
+use crate::models::AnalysisResult;
+use crate::storage::{get_job, save_job};
+use crate::utils::sanitize_repo_url;
+
+/// 后台刷新任务的状态，只能按以下方向转换：
+/// - `pending` -> `running`：后台任务开始执行
+/// - `pending` -> `failed`：任务未能启动
+/// - `running` -> `complete` 或 `failed`：分析结束
+///
+/// `complete` 和 `failed` 是终止状态。执行任务的实例被冻结或崩溃时任务不会再更新，
+/// 读取时超过 REQUEST_TIMEOUT_SECONDS 仍未结束的任务按超时转为 `failed`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Pending,
+    Running,
+    Complete,
+    Failed,
+}
+
+impl JobState {
+    /// 是否允许从当前状态转换到 `next`
+    pub fn can_transition_to(self, next: JobState) -> bool {
+        matches!(
+            (self, next),
+            (JobState::Pending, JobState::Running)
+                | (JobState::Pending, JobState::Failed)
+                | (JobState::Running, JobState::Complete)
+                | (JobState::Running, JobState::Failed)
+        )
+    }
+}
+
+// 保存在Redis中的后台刷新任务，完成后附带分析结果，失败时附带错误信息和状态码
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Job {
+    pub id: String,
+    pub repo: String,
+    pub state: JobState,
+    pub created_at: i64,
+    pub updated_at: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<AnalysisResult>,
+}
+
+impl Job {
+    /// 创建处于 pending 状态的任务，任务ID为32位随机十六进制字符串
+    pub fn new(repo: &str) -> Self {
+        let now = now();
+        Self {
+            id: format!("{:032x}", rand::rng().random::<u128>()),
+            repo: sanitize_repo_url(repo),
+            state: JobState::Pending,
+            created_at: now,
+            updated_at: now,
+            started_at: None,
+            error: None,
+            status_code: None,
+            result: None,
+        }
+    }
+
+    /// 转换到 `next` 状态，不允许的转换被忽略并返回 false
+    pub fn advance(&mut self, next: JobState) -> bool {
+        if !self.state.can_transition_to(next) {
+            warn!(job = %self.id, from = ?self.state, to = ?next, "Invalid job state transition");
+            return false;
+        }
+        self.state = next;
+        self.updated_at = now();
+        if next == JobState::Running {
+            self.started_at = Some(self.updated_at);
+        }
+        true
+    }
+
+    /// 标记任务完成并附带分析结果
+    pub fn complete(&mut self, analysis_result: AnalysisResult) {
+        if self.advance(JobState::Complete) {
+            self.result = Some(analysis_result);
+        }
+    }
+
+    /// 标记任务失败并记录错误
+    pub fn fail(&mut self, error: &RefreshError) {
+        if self.advance(JobState::Failed) {
+            self.error = Some(error.to_string());
+            self.status_code = Some(error.status_code().as_u16());
+        }
+    }
+
+    /// 将任务的当前状态写入Redis
+    pub async fn save(&self) -> Result<(), RefreshError> {
+        save_job(&self.id, &serde_json::to_string(self)?).await
+    }
+
+    /// 未结束的任务自启动（未启动时自创建）起超过 REQUEST_TIMEOUT_SECONDS 时标记为超时失败
+    pub fn fail_if_stale(&mut self, now: i64) -> bool {
+        if !matches!(self.state, JobState::Pending | JobState::Running) {
+            return false;
+        }
+        let seconds = request_timeout_seconds();
+        let started_at = self.started_at.unwrap_or(self.created_at);
+        if now - started_at <= seconds as i64 {
+            return false;
+        }
+
+        warn!(job = %self.id, state = ?self.state, "Job timed out");
+        self.fail(&RefreshError::RequestTimeout { seconds });
+        true
+    }
+
+    /// 按ID读取任务，ID格式不正确时返回参数错误；超时的任务在读取时转为失败并写回
+    pub async fn load(id: &str) -> Result<Option<Self>, RefreshError> {
+        if id.len() != 32 || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(RefreshError::InvalidParameter(format!("Invalid job id: {}", id)));
+        }
+
+        let mut job: Self = match get_job(&id.to_lowercase()).await? {
+            Some(content) => serde_json::from_str(&content)?,
+            None => return Ok(None),
+        };
+        if job.fail_if_stale(now()) {
+            if let Err(e) = job.save().await {
+                warn!(job = %job.id, "Failed to save job status: {}", e);
+            }
+        }
+
+        Ok(Some(job))
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{fake_redis, result, set_env};
+
+    #[test]
+    fn job_moves_through_its_lifecycle() {
+        let mut job = Job::new("https://github.com/demo/jobs");
+        assert_eq!(job.state, JobState::Pending);
+        assert_eq!(job.started_at, None);
+
+        // 未启动的任务不能直接完成
+        job.complete(result(Vec::new()));
+        assert_eq!(job.state, JobState::Pending);
+        assert!(job.result.is_none());
+
+        assert!(job.advance(JobState::Running));
+        assert!(job.started_at.is_some());
+        job.complete(result(Vec::new()));
+        assert_eq!(job.state, JobState::Complete);
+        assert!(job.result.is_some());
+
+        // 终止状态不再改变
+        job.fail(&RefreshError::NoCjpmToml);
+        assert_eq!(job.state, JobState::Complete);
+        assert!(job.error.is_none());
+
+        let mut failed = Job::new("https://github.com/demo/jobs");
+        assert!(failed.advance(JobState::Running));
+        failed.fail(&RefreshError::NoCjpmToml);
+        assert_eq!(failed.state, JobState::Failed);
+        assert_eq!(failed.status_code, Some(RefreshError::NoCjpmToml.status_code().as_u16()));
+        assert!(failed.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn stale_job_is_failed_on_load() {
+        fake_redis().await;
+        let _env = set_env(&[("REQUEST_TIMEOUT_SECONDS", Some("60"))]).await;
+
+        let mut running = Job::new("https://github.com/demo/stale");
+        assert!(running.advance(JobState::Running));
+        running.started_at = Some(now() - 120);
+        running.save().await.unwrap();
+
+        let mut fresh = Job::new("https://github.com/demo/stale");
+        assert!(fresh.advance(JobState::Running));
+        fresh.save().await.unwrap();
+
+        let loaded = Job::load(&running.id).await.unwrap().unwrap();
+        assert_eq!(loaded.state, JobState::Failed);
+        assert_eq!(loaded.status_code, Some(504));
+        // 失败状态已经写回Redis
+        let reloaded = Job::load(&running.id).await.unwrap().unwrap();
+        assert_eq!(reloaded.state, JobState::Failed);
+
+        let loaded = Job::load(&fresh.id).await.unwrap().unwrap();
+        assert_eq!(loaded.state, JobState::Running);
+    }
+}
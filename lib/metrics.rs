@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+use crate::analysis::summarize;
+use crate::models::{AnalysisResult, AnalyzerCount, CacheMetrics};
+
+// 汇总结果中保留的检查器数量
+const TOP_ANALYZERS: usize = 10;
+
+/// 逐个累加已缓存的分析结果，得到跨仓库的汇总统计
+#[derive(Debug, Default)]
+pub struct MetricsCollector {
+    metrics: CacheMetrics,
+    by_analyzer: HashMap<String, usize>,
+}
+
+impl MetricsCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 累加一个仓库的分析结果
+    pub fn add(&mut self, analysis_result: &AnalysisResult) {
+        // 旧的缓存数据没有 summary，根据问题列表重新统计
+        let recomputed;
+        let summary = if analysis_result.summary.total == 0 && !analysis_result.cjlint.is_empty() {
+            recomputed = summarize(&analysis_result.cjlint);
+            &recomputed
+        } else {
+            &analysis_result.summary
+        };
+
+        self.metrics.repos += 1;
+        self.metrics.findings += summary.total;
+        self.metrics.mandatory += summary.mandatory;
+        self.metrics.suggestions += summary.suggestions;
+        for (analyzer, count) in &summary.by_analyzer {
+            *self.by_analyzer.entry(analyzer.clone()).or_insert(0) += count;
+        }
+    }
+
+    /// 结束统计，检查器按问题数量降序排列，数量相同时按名称排序
+    pub fn finish(self) -> CacheMetrics {
+        let mut top_analyzers: Vec<AnalyzerCount> = self
+            .by_analyzer
+            .into_iter()
+            .map(|(analyzer, count)| AnalyzerCount { analyzer, count })
+            .collect();
+        top_analyzers.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.analyzer.cmp(&b.analyzer))
+        });
+        top_analyzers.truncate(TOP_ANALYZERS);
+
+        CacheMetrics {
+            top_analyzers,
+            ..self.metrics
+        }
+    }
+}
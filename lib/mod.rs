@@ -2,4 +2,22 @@ pub mod models;
 pub mod repository;
 pub mod analysis;
 pub mod utils;
-pub mod storage; 
\ No newline at end of file
+pub mod storage;
+pub mod response;
+pub mod error;
+pub mod config;
+pub mod report;
+pub mod rate_limit;
+pub mod auth;
+pub mod baseline;
+pub mod badge;
+pub mod metrics;
+pub mod callback;
+pub mod pipeline;
+pub mod rules;
+pub mod jobs;
+
+// 接口处理函数的测试位于各自的 bin 中，通过 dev-dependencies 开启 test-support 使用这些辅助函数
+#[cfg(any(test, feature = "test-support"))]
+#[doc(hidden)]
+pub mod test_support;
@@ -1,6 +1,11 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use tempfile::TempDir;
+use tokio::sync::OwnedSemaphorePermit;
+use crate::error::RefreshError;
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub enum DefectLevel {
     #[serde(rename = "MANDATORY")]
     Mandatory,
@@ -10,21 +15,87 @@ pub enum DefectLevel {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AnalysisResultItem {
+    // 相对仓库根目录的路径，不在仓库内的路径保持cjlint的原始输出
     pub file: String,
+    // cjlint输出的原始路径，便于调试时定位临时目录中的文件
+    #[serde(rename = "absolutePath", default, skip_serializing_if = "Option::is_none")]
+    pub absolute_path: Option<String>,
     pub line: i32,
+    // 以下带默认值的字段在部分cjlint版本的输出中可能缺失
+    #[serde(default)]
     pub column: i32,
-    #[serde(rename = "endLine")]
+    #[serde(rename = "endLine", default)]
     pub end_line: i32,
-    #[serde(rename = "endColumn")]
+    #[serde(rename = "endColumn", default)]
     pub end_column: i32,
     #[serde(rename = "analyzerName")]
     pub analyzer_name: String,
+    #[serde(default)]
     pub description: String,
     #[serde(rename = "defectLevel")]
     pub defect_level: DefectLevel,
-    #[serde(rename = "defectType")]
+    // 按级别覆盖配置修改前cjlint报告的级别，只在级别被修改时填充
+    #[serde(rename = "originalDefectLevel", default, skip_serializing_if = "Option::is_none")]
+    pub original_defect_level: Option<DefectLevel>,
+    #[serde(rename = "defectType", default)]
     pub defect_type: String,
+    #[serde(default)]
     pub language: String,
+    // 问题所属的包，由分析流程根据文件路径填充
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    // 同一问题被cjlint重复报告的次数，只在去重时出现多次的情况下填充
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<usize>,
+    // 问题所在行的源码，只在请求 include_snippets 时填充
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+}
+
+// 与基准版本对比的统计信息
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiffSummary {
+    pub base_commit: String,
+    pub added: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+}
+
+// 分析结果的统计信息
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AnalysisSummary {
+    pub total: usize,
+    pub mandatory: usize,
+    pub suggestions: usize,
+    pub by_analyzer: BTreeMap<String, usize>,
+    // 按问题类型统计，旧的缓存数据没有该字段
+    #[serde(default)]
+    pub by_type: BTreeMap<String, usize>,
+    // 出现次数最多的检查器与问题类型组合
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub top_issues: Vec<IssueCount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<DiffSummary>,
+    // 去重时合并掉的重复问题数量
+    #[serde(default)]
+    pub duplicates: usize,
+    // 被仓库中 .cjlintignore 排除的问题数量
+    #[serde(default)]
+    pub ignored: usize,
+    // 因已记录在基线中而被排除的问题数量
+    #[serde(default)]
+    pub baseline_suppressed: usize,
+    // 因不匹配 include_paths 或匹配 exclude_paths 而被排除的文件和问题数量
+    #[serde(default)]
+    pub excluded_files: usize,
+    #[serde(default)]
+    pub excluded_findings: usize,
+    // 只返回相对基准版本变更过的文件时，其余文件中被排除的问题数量
+    #[serde(default)]
+    pub not_changed: usize,
+    // 按请求忽略的问题数量，按检查器统计
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub suppressed: BTreeMap<String, usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -33,6 +104,168 @@ pub struct AnalysisResult {
     pub created_at: i64,
     pub commit: String,
     pub package_name: String,
+    #[serde(default)]
+    pub packages: Vec<String>,
+    // 旧的缓存数据没有该字段
+    #[serde(default)]
+    pub summary: AnalysisSummary,
+    // cjlint非零退出但仍产出报告时的退出码，此时结果可能不完整
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cjlint_exit_code: Option<i32>,
+    // 产出该报告的cjlint版本，旧的缓存数据没有该字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cjlint_version: Option<String>,
+    // 旧的缓存数据没有该字段，各项耗时为0
+    #[serde(default)]
+    pub timings: AnalysisTimings,
+    // 只分析了仓库中的某个子目录时为该目录相对仓库根目录的路径
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    // 请求指定的规则配置，便于复现结果
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_config: Option<AppliedRuleConfig>,
+    // 主包cjpm.toml中声明的工具链信息，旧的缓存数据没有该字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_metadata: Option<PackageMetadata>,
+    // 返回的问题超过 MAX_FINDINGS 被截断时为 true，summary 仍为截断前的统计
+    #[serde(default)]
+    pub truncated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_before_truncation: Option<usize>,
+    // 分页返回时的分页信息，不会写入缓存
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<PageInfo>,
+    // 分析范围内的仓颉源文件数量和总行数，旧的缓存数据为0
+    #[serde(default)]
+    pub files_analyzed: usize,
+    #[serde(default)]
+    pub lines_analyzed: usize,
+    // 每千行代码的问题数量，没有源码时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defect_density: Option<f64>,
+    // REDIS_OPTIONAL 开启且结果未能写入缓存时为 false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached: Option<bool>,
+    // 管理员要求保留克隆目录时目录在服务器上的路径，不会写入缓存
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retained_path: Option<String>,
+    // 指定 changed_only 时相对基准版本变更过的仓颉源文件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changed_files: Option<Vec<String>>,
+}
+
+// 返回给客户端的分页信息
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PageInfo {
+    // 分页前的问题总数
+    pub total: usize,
+    pub offset: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    // 下一页的起始位置，已经是最后一页时为空
+    pub next_offset: Option<usize>,
+}
+
+// 分页参数，未指定 limit 时返回从 offset 开始的全部问题
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Pagination {
+    pub limit: Option<usize>,
+    pub offset: usize,
+}
+
+impl Pagination {
+    /// 解析 limit 和 offset 参数，两者都未指定时不分页
+    pub fn parse(limit: Option<&str>, offset: Option<&str>) -> Result<Option<Self>, RefreshError> {
+        if limit.is_none() && offset.is_none() {
+            return Ok(None);
+        }
+
+        let limit = match limit {
+            None => None,
+            Some(raw) => match raw.trim().parse::<usize>() {
+                Ok(limit) if limit > 0 => Some(limit),
+                _ => {
+                    return Err(RefreshError::InvalidParameter(format!(
+                        "limit expects a positive integer, got {}",
+                        raw
+                    )));
+                }
+            },
+        };
+        let offset = match offset {
+            None => 0,
+            Some(raw) => raw.trim().parse::<usize>().map_err(|_| {
+                RefreshError::InvalidParameter(format!(
+                    "offset expects a non-negative integer, got {}",
+                    raw
+                ))
+            })?,
+        };
+
+        Ok(Some(Self { limit, offset }))
+    }
+}
+
+// 分析时实际使用的规则配置，source 为 inline 或仓库中的配置文件路径
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedRuleConfig {
+    pub source: String,
+    pub sha256: String,
+}
+
+// 从cjpm.toml中读取的包信息，清单中未声明的字段为空
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackageMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    // 包要求的cjc编译器版本，对应 `cjc-version`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cjc_version: Option<String>,
+    // 对应 `output-type`，如 executable、static、dynamic
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_type: Option<String>,
+    // `[target.<triple>]` 中单独配置的目标平台
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+}
+
+// 分析各阶段的耗时（毫秒）
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct AnalysisTimings {
+    pub clone_ms: u64,
+    pub lint_ms: u64,
+    pub total_ms: u64,
+}
+
+// 试运行的结果，只包含克隆和识别包得到的信息
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DryRunResult {
+    pub commit: String,
+    pub package_name: String,
+    pub packages: Vec<String>,
+    pub cj_file_count: usize,
+}
+
+// 分析历史中的一条记录，只保留统计信息
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub commit: String,
+    pub created_at: i64,
+    pub total: usize,
+    pub mandatory: usize,
+    pub suggestions: usize,
+}
+
+impl From<&AnalysisResult> for HistoryEntry {
+    fn from(analysis_result: &AnalysisResult) -> Self {
+        Self {
+            commit: analysis_result.commit.clone(),
+            created_at: analysis_result.created_at,
+            total: analysis_result.summary.total,
+            mandatory: analysis_result.summary.mandatory,
+            suggestions: analysis_result.summary.suggestions,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -43,9 +276,364 @@ pub struct ApiResponse<T> {
     pub error: Option<String>,
 }
 
-// 定义一个结构体来存储克隆结果
-#[derive(Debug, Clone)]
+// cjlint支持的检查器及其默认级别
+#[derive(Debug, Serialize)]
+pub struct AnalyzerInfo {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_level: Option<DefectLevel>,
+}
+
+// 批量分析中单个仓库的结果，一个仓库失败不影响其他仓库
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchEntry {
+    pub success: bool,
+    pub status: u16,
+    pub data: Option<AnalysisResult>,
+    pub error: Option<String>,
+}
+
+// 健康检查结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HealthStatus {
+    pub cjlint_ready: bool,
+    pub redis_ok: bool,
+    pub version: String,
+}
+
+// 所有已缓存仓库的汇总统计
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CacheMetrics {
+    pub repos: usize,
+    pub findings: usize,
+    pub mandatory: usize,
+    pub suggestions: usize,
+    // 按问题数量降序排列的检查器
+    pub top_analyzers: Vec<AnalyzerCount>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IssueCount {
+    pub analyzer: String,
+    pub defect_type: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnalyzerCount {
+    pub analyzer: String,
+    pub count: usize,
+}
+
+// 定义一个结构体来存储克隆结果，repo_dir 被丢弃时删除克隆目录
+#[derive(Debug)]
 pub struct CloneResult {
+    pub repo_dir: TempDir,
     pub repo_path: String,
     pub commit_hash: String,
-} 
\ No newline at end of file
+    // 克隆名额，与克隆结果一起丢弃时释放，因此在克隆目录删除之后才会被其他请求使用
+    pub clone_slot: OwnedSemaphorePermit,
+}
+
+// 刷新接口的请求参数，GET请求来自查询参数，POST请求来自JSON请求体
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RefreshRequest {
+    pub repo: Option<String>,
+    #[serde(rename = "ref")]
+    pub reference: Option<String>,
+    pub branch: Option<String>,
+    pub commit: Option<String>,
+    pub base: Option<String>,
+    pub level: Option<String>,
+    pub language: Option<String>,
+    pub format: Option<String>,
+    pub group_by: Option<String>,
+    pub sort: Option<String>,
+    pub shape: Option<String>,
+    pub fields: Option<String>,
+    pub junit_suggestions: Option<String>,
+    pub ignore_analyzers: Option<String>,
+    pub ignore_types: Option<String>,
+    pub include_paths: Option<String>,
+    pub exclude_paths: Option<String>,
+    pub severity_overrides: Option<String>,
+    pub extra_args: Option<String>,
+    pub submodules: Option<bool>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub depth: Option<String>,
+    pub dry_run: Option<bool>,
+    pub force: Option<bool>,
+    pub keep_repo: Option<bool>,
+    #[serde(rename = "async")]
+    pub async_mode: Option<bool>,
+    pub include_snippets: Option<bool>,
+    pub changed_only: Option<bool>,
+    pub raw: Option<bool>,
+    pub suppress_baseline: Option<bool>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub limit: Option<String>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub offset: Option<String>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub max_findings: Option<String>,
+    pub callback_url: Option<String>,
+    pub path: Option<String>,
+    pub local_path: Option<String>,
+    // 内联的规则配置只能通过POST请求体提供
+    pub rule_config: Option<serde_json::Value>,
+    pub rule_config_path: Option<String>,
+}
+
+impl RefreshRequest {
+    /// 从查询参数构造请求，忽略无关的参数；布尔参数只接受 true、false、1、0
+    pub fn from_query(hash_query: &HashMap<String, String>) -> Result<Self, RefreshError> {
+        let get = |name: &str| hash_query.get(name).cloned();
+        let flag = |name: &str| parse_flag(name, hash_query.get(name));
+        Ok(Self {
+            repo: get("repo"),
+            reference: get("ref"),
+            branch: get("branch"),
+            commit: get("commit"),
+            base: get("base"),
+            level: get("level"),
+            language: get("language"),
+            format: get("format"),
+            group_by: get("group_by"),
+            sort: get("sort"),
+            shape: get("shape"),
+            fields: get("fields"),
+            junit_suggestions: get("junit_suggestions"),
+            ignore_analyzers: get("ignore_analyzers"),
+            ignore_types: get("ignore_types"),
+            include_paths: get("include_paths"),
+            exclude_paths: get("exclude_paths"),
+            severity_overrides: get("severity_overrides"),
+            extra_args: get("extra_args"),
+            depth: get("depth"),
+            submodules: flag("submodules")?,
+            dry_run: flag("dry_run")?,
+            force: flag("force")?,
+            keep_repo: flag("keep_repo")?,
+            async_mode: flag("async")?,
+            include_snippets: flag("include_snippets")?,
+            changed_only: flag("changed_only")?,
+            raw: flag("raw")?,
+            suppress_baseline: flag("suppress_baseline")?,
+            limit: get("limit"),
+            offset: get("offset"),
+            max_findings: get("max_findings"),
+            callback_url: get("callback_url"),
+            path: get("path"),
+            local_path: get("local_path"),
+            rule_config: None,
+            rule_config_path: get("rule_config_path"),
+        })
+    }
+
+    /// 从POST请求的JSON请求体构造请求
+    pub fn from_json(body: &[u8]) -> Result<Self, RefreshError> {
+        serde_json::from_slice(body).map_err(|e| {
+            RefreshError::InvalidParameter(format!("Invalid request body: {}", e))
+        })
+    }
+}
+
+/// 解析查询参数中的布尔值，其他取值返回参数错误而不是当作 false
+fn parse_flag(name: &str, value: Option<&String>) -> Result<Option<bool>, RefreshError> {
+    match value.map(String::as_str) {
+        None => Ok(None),
+        Some("1" | "true") => Ok(Some(true)),
+        Some("0" | "false") => Ok(Some(false)),
+        Some(value) => Err(RefreshError::InvalidParameter(format!(
+            "Invalid {}: {}, expected one of true, false, 1, 0",
+            name, value
+        ))),
+    }
+}
+
+// JSON请求体中的数值参数既可以写成数字，也可以与查询参数一样写成字符串
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrNumber {
+    String(String),
+    Number(serde_json::Number),
+}
+
+fn string_or_number<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<StringOrNumber>::deserialize(deserializer)?.map(|value| match value {
+        StringOrNumber::String(value) => value,
+        StringOrNumber::Number(value) => value.to_string(),
+    }))
+}
+
+// 返回结果时按问题级别过滤
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum LevelFilter {
+    #[default]
+    All,
+    Mandatory,
+    Suggestions,
+}
+
+impl FromStr for LevelFilter {
+    type Err = RefreshError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(LevelFilter::All),
+            "mandatory" => Ok(LevelFilter::Mandatory),
+            "suggestions" => Ok(LevelFilter::Suggestions),
+            _ => Err(RefreshError::InvalidParameter(format!(
+                "Invalid level: {}, expected one of mandatory, suggestions, all",
+                s
+            ))),
+        }
+    }
+}
+
+// 返回结果中问题的排列顺序
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SortOrder {
+    // 按文件、行、列排列
+    #[default]
+    Position,
+    // 强制问题在前，同级别内按位置排列
+    Severity,
+}
+
+impl FromStr for SortOrder {
+    type Err = RefreshError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "position" => Ok(SortOrder::Position),
+            "severity" => Ok(SortOrder::Severity),
+            _ => Err(RefreshError::InvalidParameter(format!(
+                "Invalid sort: {}, expected one of position, severity",
+                s
+            ))),
+        }
+    }
+}
+
+// 一次cjlint运行的结果
+#[derive(Debug)]
+pub struct CjlintRun {
+    pub items: Vec<AnalysisResultItem>,
+    pub exit_code: i32,
+}
+
+// 克隆后需要检出的版本
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum CloneTarget {
+    #[default]
+    Default,
+    Branch(String),
+    Ref(String),
+    Commit(String),
+}
+
+impl CloneTarget {
+    /// 根据版本字符串推断目标，形如提交哈希的视为提交，否则视为分支或标签
+    pub fn from_revision(revision: &str) -> Self {
+        let is_hash = (7..=40).contains(&revision.len())
+            && revision.chars().all(|c| c.is_ascii_hexdigit());
+        if is_hash {
+            CloneTarget::Commit(revision.to_string())
+        } else {
+            CloneTarget::Ref(revision.to_string())
+        }
+    }
+}
+
+// 克隆仓库时的选项，包含访问令牌，因此不实现 Debug 以免被打印到日志
+#[derive(Clone, Default)]
+pub struct CloneOptions {
+    pub target: CloneTarget,
+    pub token: Option<String>,
+    // 是否初始化并检出子模块，默认关闭以保持克隆速度
+    pub submodules: bool,
+    // 抓取深度，0 表示完整历史，未指定时按检出目标选择默认深度
+    pub depth: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn post_body_matches_equivalent_query() {
+        let from_query = RefreshRequest::from_query(&query(&[
+            ("repo", "https://github.com/demo/repo"),
+            ("level", "mandatory"),
+            ("ref", "v1"),
+            ("extra_args", "-j 4"),
+            ("depth", "3"),
+            ("submodules", "1"),
+            ("limit", "10"),
+            ("offset", "5"),
+            ("max_findings", "100"),
+        ]))
+        .unwrap();
+
+        let body = br#"{
+            "repo": "https://github.com/demo/repo",
+            "level": "mandatory",
+            "ref": "v1",
+            "extra_args": "-j 4",
+            "depth": 3,
+            "submodules": true,
+            "limit": "10",
+            "offset": 5,
+            "max_findings": 100
+        }"#;
+        assert_eq!(RefreshRequest::from_json(body).unwrap(), from_query);
+    }
+
+    #[test]
+    fn invalid_flags_are_rejected() {
+        for value in ["yes", "TRUE", ""] {
+            assert!(matches!(
+                RefreshRequest::from_query(&query(&[("submodules", value)])),
+                Err(RefreshError::InvalidParameter(_))
+            ));
+        }
+        assert!(RefreshRequest::from_json(br#"{"depth": [1]}"#).is_err());
+    }
+
+    #[test]
+    fn unknown_or_malformed_body_fields_are_rejected() {
+        for body in [&br#"{"repo": [1]}"#[..], br#"{"reop": "x"}"#, b"not json"] {
+            assert!(matches!(
+                RefreshRequest::from_json(body),
+                Err(RefreshError::InvalidParameter(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn pagination_requires_a_positive_limit() {
+        assert_eq!(Pagination::parse(None, None).unwrap(), None);
+        assert_eq!(
+            Pagination::parse(None, Some("3")).unwrap(),
+            Some(Pagination { limit: None, offset: 3 })
+        );
+        for (limit, offset) in [(Some("0"), None), (Some("-1"), None), (None, Some("-1"))] {
+            assert!(matches!(
+                Pagination::parse(limit, offset),
+                Err(RefreshError::InvalidParameter(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,675 @@
+use git2::Repository;
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::fs;
+use tokio::time::timeout;
+use tracing::warn;
+use crate::analysis::{
+    apply_ignore_patterns, assign_packages, attach_snippets, cjlint_version, dedup_findings,
+    defect_density, lint_roots, parse_ignore_file, process_analysis_result, run_cjlint_parallel,
+    sort_findings, summarize,
+};
+use crate::config::{cache_disabled, request_timeout_seconds};
+use crate::error::RefreshError;
+use crate::models::{AnalysisResult, AnalysisSummary, AnalysisTimings, CloneOptions};
+use crate::repository::{
+    changed_cangjie_files, clone_repository, find_package_metadata, find_packages,
+    measure_cangjie_sources, repo_name, resolve_remote_commit, retain_repo, resolve_subdir,
+    validate_repo_url,
+};
+use crate::rules::RuleConfig;
+use crate::storage::{get_commit_from_redis, save_commit_to_redis, tolerate_redis_failure};
+
+// 分析结果及返回给调用方的提示信息
+pub struct RefreshOutcome {
+    pub analysis_result: AnalysisResult,
+    pub message: &'static str,
+    pub from_cache: bool,
+    // 请求保留克隆目录时目录的路径
+    pub retained_path: Option<PathBuf>,
+    // 指定 changed_since 时相对该版本变更过的仓颉源文件
+    pub changed_files: Option<BTreeSet<String>>,
+}
+
+// 分析流程中可按请求开启的行为
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnalyzeFlags<'a> {
+    // 为每个问题附带源码片段
+    pub include_snippets: bool,
+    // 分析后保留克隆目录
+    pub keep_repo: bool,
+    // 跳过缓存命中的快速返回，总是重新克隆和分析
+    pub force: bool,
+    // 同时列出相对该版本变更过的仓颉源文件，需要克隆目录因此不使用缓存命中的快速返回
+    pub changed_since: Option<&'a str>,
+}
+
+/// 优先读取按提交缓存的结果，未命中时克隆并分析，新结果按提交哈希写入缓存
+pub async fn load_or_analyze(
+    repo: &str,
+    clone_options: &CloneOptions,
+    subdir: Option<&Path>,
+    rule_config: Option<&RuleConfig>,
+    extra_args: &[String],
+    flags: AnalyzeFlags<'_>,
+) -> Result<RefreshOutcome, RefreshError> {
+    let cache_key = |commit: &str| {
+        commit_cache_key(
+            commit,
+            clone_options,
+            subdir,
+            rule_config,
+            extra_args,
+            flags.include_snippets,
+        )
+    };
+    // 强制刷新、保留克隆目录或计算变更文件时必须实际克隆
+    if !cache_disabled() && !flags.force && !flags.keep_repo && flags.changed_since.is_none() {
+        if let Some(analysis_result) = find_cached_commit(repo, clone_options, &cache_key).await {
+            return Ok(RefreshOutcome {
+                analysis_result,
+                message: "Analysis loaded from cache",
+                from_cache: true,
+                retained_path: None,
+                changed_files: None,
+            });
+        }
+    }
+
+    let mut outcome = analyze(repo, clone_options, subdir, rule_config, extra_args, flags).await?;
+
+    let serialized = serde_json::to_string(&outcome.analysis_result)?;
+    let key = cache_key(&outcome.analysis_result.commit);
+    let saved = save_commit_to_redis(repo, &key, &serialized).await;
+    if !tolerate_redis_failure(saved, "save commit result")? {
+        outcome.analysis_result.cached = Some(false);
+    }
+
+    Ok(outcome)
+}
+
+/// 限制 `future` 在 REQUEST_TIMEOUT_SECONDS 内完成，超时时丢弃它并返回 RequestTimeout
+///
+/// 丢弃的 future 中的cjlint子进程随之结束；阻塞线程上的克隆收到取消标记后中止，
+/// 其克隆目录和名额在线程退出时释放
+pub async fn with_request_timeout<T>(
+    future: impl Future<Output = Result<T, RefreshError>>,
+) -> Result<T, RefreshError> {
+    let seconds = request_timeout_seconds();
+    match timeout(Duration::from_secs(seconds), future).await {
+        Ok(result) => result,
+        Err(_) => Err(RefreshError::RequestTimeout { seconds }),
+    }
+}
+
+/// 远端最新提交已经分析过时直接返回缓存结果，查询失败时返回 None 以继续完整分析
+async fn find_cached_commit(
+    repo: &str,
+    clone_options: &CloneOptions,
+    cache_key: impl Fn(&str) -> String,
+) -> Option<AnalysisResult> {
+    let commit = match resolve_remote_commit(repo, clone_options).await {
+        Ok(Some(commit)) => commit,
+        Ok(None) => return None,
+        Err(e) => {
+            warn!("Failed to resolve remote commit: {}", e);
+            return None;
+        }
+    };
+
+    match get_commit_from_redis(repo, &cache_key(&commit)).await {
+        Ok(Some(content)) => serde_json::from_str(&content).ok(),
+        Ok(None) => None,
+        Err(e) => {
+            warn!("Failed to read commit cache: {}", e);
+            None
+        }
+    }
+}
+
+/// 按提交缓存时使用的标识，包含子模块、只分析子目录、使用自定义规则、带额外cjlint参数
+/// 或附带源码片段的结果与完整结果分开缓存
+fn commit_cache_key(
+    commit: &str,
+    clone_options: &CloneOptions,
+    subdir: Option<&Path>,
+    rule_config: Option<&RuleConfig>,
+    extra_args: &[String],
+    include_snippets: bool,
+) -> String {
+    let mut key = commit.to_string();
+    if clone_options.submodules {
+        key.push_str("_submodules");
+    }
+    if let Some(subdir) = subdir {
+        key.push_str(&format!("_path_{}", subdir.display()));
+    }
+    if let Some(rule_config) = rule_config {
+        key.push('_');
+        key.push_str(&rule_config.cache_suffix());
+    }
+    if !extra_args.is_empty() {
+        key.push_str(&format!("_args_{}", extra_args.join(" ")));
+    }
+    if include_snippets {
+        key.push_str("_snippets");
+    }
+    key
+}
+
+/// 克隆仓库并运行cjlint，得到完整的分析结果
+async fn analyze(
+    repo: &str,
+    clone_options: &CloneOptions,
+    subdir: Option<&Path>,
+    rule_config: Option<&RuleConfig>,
+    extra_args: &[String],
+    flags: AnalyzeFlags<'_>,
+) -> Result<RefreshOutcome, RefreshError> {
+    let started_at = Instant::now();
+
+    // 克隆目录随 clone_result.repo_dir 一起删除，出错提前返回时同样会清理；
+    // 克隆名额在 clone_result 丢弃时，即函数返回时释放
+    let clone_started_at = Instant::now();
+    let clone_result = clone_repository(repo, clone_options).await?;
+    let clone_ms = clone_started_at.elapsed().as_millis() as u64;
+
+    let repo_name = repo_name(&validate_repo_url(repo)?);
+    let mut outcome = analyze_dir(
+        &clone_result.repo_path,
+        &repo_name,
+        clone_result.commit_hash.clone(),
+        subdir,
+        rule_config,
+        extra_args,
+        flags.include_snippets,
+    )
+    .await?;
+    outcome.analysis_result.timings.clone_ms = clone_ms;
+    if let Some(base) = flags.changed_since {
+        outcome.changed_files =
+            Some(changed_cangjie_files(&clone_result.repo_path, base, clone_options).await?);
+    }
+    outcome.analysis_result.timings.total_ms = started_at.elapsed().as_millis() as u64;
+
+    if flags.keep_repo {
+        outcome.retained_path = retain_repo(clone_result.repo_dir).await?;
+    } else if let Err(e) = clone_result.repo_dir.close() {
+        warn!("Failed to clean up repository: {}", e);
+    }
+
+    Ok(outcome)
+}
+
+/// 直接分析服务器上已有的目录，不克隆也不读写缓存
+///
+/// 目录位于git仓库中时使用其HEAD提交，否则提交记为 `local`
+pub async fn analyze_local(
+    dir: &Path,
+    subdir: Option<&Path>,
+    rule_config: Option<&RuleConfig>,
+    extra_args: &[String],
+    include_snippets: bool,
+) -> Result<RefreshOutcome, RefreshError> {
+    let commit = Repository::discover(dir)
+        .and_then(|repo| repo.head()?.peel_to_commit().map(|commit| commit.id().to_string()))
+        .unwrap_or_else(|_| "local".to_string());
+    let name = dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "local".to_string());
+
+    analyze_dir(
+        &dir.to_string_lossy(),
+        &name,
+        commit,
+        subdir,
+        rule_config,
+        extra_args,
+        include_snippets,
+    )
+    .await
+}
+
+/// 在已经准备好的目录上运行cjlint，问题路径相对该目录
+async fn analyze_dir(
+    repo_path: &str,
+    repo_name: &str,
+    commit: String,
+    subdir: Option<&Path>,
+    rule_config: Option<&RuleConfig>,
+    extra_args: &[String],
+    include_snippets: bool,
+) -> Result<RefreshOutcome, RefreshError> {
+    let started_at = Instant::now();
+
+    let packages = find_packages(repo_path.to_string(), repo_name).await?;
+    let package_metadata = find_package_metadata(&packages[0].0).await;
+
+    // 指定子目录时只检查该目录，各个包仍按完整仓库查找，问题路径仍相对仓库根目录
+    let (lint_dirs, source_root) = match subdir {
+        Some(subdir) => {
+            let dir = resolve_subdir(repo_path, subdir)?;
+            (vec![dir.clone()], dir)
+        }
+        None => (lint_roots(&packages), PathBuf::from(repo_path)),
+    };
+
+    // 自定义规则写入临时的配置目录，通过 -c 传给cjlint，目录在函数返回时删除
+    let mut lint_args = extra_args.to_vec();
+    let (_rule_config_dir, applied_rule_config) = match rule_config {
+        Some(rule_config) => {
+            let (dir, applied) = rule_config.prepare(repo_path).await?;
+            lint_args.push("-c".to_string());
+            lint_args.push(dir.path().to_string_lossy().to_string());
+            (Some(dir), Some(applied))
+        }
+        None => (None, None),
+    };
+
+    // 没有源文件时不运行cjlint，避免返回令人困惑的空报告
+    let (files_analyzed, lines_analyzed) =
+        measure_cangjie_sources(&source_root.to_string_lossy())?;
+    let has_sources = files_analyzed > 0;
+    let lint_started_at = Instant::now();
+    let (analysis_result, exit_code) = if has_sources {
+        // 使用 cjlint 并发检查各个包
+        let cjlint_run = run_cjlint_parallel(lint_dirs, &lint_args).await?;
+        (cjlint_run.items, cjlint_run.exit_code)
+    } else {
+        (Vec::new(), 0)
+    };
+    let lint_ms = lint_started_at.elapsed().as_millis() as u64;
+
+    // 处理file字段，去除repo_path前缀
+    let mut processed_analysis_result = process_analysis_result(analysis_result, repo_path);
+    let ignore_file = Path::new(repo_path).join(".cjlintignore");
+    let ignore_patterns = match fs::read_to_string(ignore_file).await {
+        Ok(content) => parse_ignore_file(&content),
+        Err(_) => Vec::new(),
+    };
+    let ignored = apply_ignore_patterns(&mut processed_analysis_result, &ignore_patterns);
+    // 同一文件被多个包引用时cjlint会重复报告同一问题
+    let duplicates = dedup_findings(&mut processed_analysis_result);
+    // cjlint的输出顺序在多次运行之间并不稳定
+    sort_findings(&mut processed_analysis_result);
+    assign_packages(&mut processed_analysis_result, &packages, repo_path);
+    // 片段需要在删除克隆目录之前读取
+    if include_snippets {
+        attach_snippets(&mut processed_analysis_result, repo_path).await;
+    }
+    let summary = AnalysisSummary {
+        duplicates,
+        ignored,
+        ..summarize(&processed_analysis_result)
+    };
+
+    let summary_total = summary.total;
+    let analysis_result = AnalysisResult {
+        cjlint: processed_analysis_result,
+        created_at: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64,
+        commit,
+        package_name: packages[0].1.clone(),
+        packages: packages.into_iter().map(|(_, name)| name).collect(),
+        path: subdir.map(|subdir| subdir.to_string_lossy().to_string()),
+        rule_config: applied_rule_config,
+        summary,
+        cjlint_exit_code: (exit_code != 0).then_some(exit_code),
+        cjlint_version: Some(cjlint_version().await),
+        timings: AnalysisTimings {
+            clone_ms: 0,
+            lint_ms,
+            total_ms: started_at.elapsed().as_millis() as u64,
+        },
+        package_metadata: Some(package_metadata),
+        truncated: false,
+        total_before_truncation: None,
+        page: None,
+        cached: None,
+        retained_path: None,
+        changed_files: None,
+        files_analyzed,
+        lines_analyzed,
+        defect_density: defect_density(summary_total, lines_analyzed),
+    };
+
+    Ok(RefreshOutcome {
+        analysis_result,
+        message: if has_sources {
+            "Analysis completed successfully"
+        } else {
+            "no Cangjie source files found"
+        },
+        from_cache: false,
+        retained_path: None,
+        changed_files: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::validate_local_path;
+    use crate::test_support::{
+        commit_file, delay_fake_cjlint, fake_cjlint, fake_cjlint_calls, fake_redis,
+        fixture_package, fixture_repo, set_env, write_file,
+    };
+
+    const REPORT: &str = r#"[{
+        "file": "{dir}/src/main.cj",
+        "line": 3,
+        "column": 5,
+        "endLine": 3,
+        "endColumn": 12,
+        "analyzerName": "G.FMT.01",
+        "description": "line too long",
+        "defectLevel": "SUGGESTIONS",
+        "defectType": "FMT",
+        "language": "Cangjie"
+    }]"#;
+
+    #[test]
+    fn commit_cache_key_includes_extra_args() {
+        let commit = "0123456789abcdef0123456789abcdef01234567";
+        let options = CloneOptions::default();
+        let id = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+            commit_cache_key(commit, &options, None, None, &args, false)
+        };
+
+        assert_eq!(id(&[]), commit);
+        let excluded = id(&["-e", "src/generated"]);
+        assert!(excluded.starts_with(commit));
+        assert_ne!(excluded, id(&["-e", "src/vendor"]));
+        assert_ne!(excluded, id(&["-e", "src/generated", "-j", "4"]));
+        assert_eq!(excluded, id(&["-e", "src/generated"]));
+    }
+
+    #[tokio::test]
+    async fn timings_cover_clone_and_lint() {
+        let home = tempfile::tempdir().unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        fake_redis().await;
+        fake_cjlint(home.path(), Some("[]"), 0);
+        delay_fake_cjlint(home.path(), 0.1);
+        let repo = fixture_package("timings");
+        let home_path = home.path().to_string_lossy().to_string();
+        let temp_path = temp.path().to_string_lossy().to_string();
+        let _env = set_env(&[
+            ("CANGJIE_HOME", Some(home_path.as_str())),
+            ("TEMP_DIR", Some(temp_path.as_str())),
+            ("ALLOWED_REPO_HOSTS", Some("fixture.test")),
+        ])
+        .await;
+
+        let options = CloneOptions {
+            depth: Some(0),
+            ..Default::default()
+        };
+        let outcome = load_or_analyze(&repo, &options, None, None, &[], AnalyzeFlags::default())
+            .await
+            .unwrap();
+        let timings = outcome.analysis_result.timings;
+        assert!(timings.lint_ms >= 100);
+        assert!(timings.clone_ms + timings.lint_ms <= timings.total_ms);
+
+        // 没有耗时的旧缓存结果仍能读取
+        let mut cached = serde_json::to_value(&outcome.analysis_result).unwrap();
+        cached.as_object_mut().unwrap().remove("timings");
+        let cached: AnalysisResult = serde_json::from_value(cached).unwrap();
+        assert_eq!(cached.timings.total_ms, 0);
+    }
+
+    #[tokio::test]
+    async fn package_without_sources_skips_cjlint() {
+        let home = tempfile::tempdir().unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        fake_redis().await;
+        fake_cjlint(home.path(), Some("[]"), 0);
+        let (repo, url) = fixture_repo("no_sources");
+        commit_file(&repo, "cjpm.toml", "[package]\nname = \"empty\"\n");
+        commit_file(&repo, "README.md", "no sources\n");
+        let home_path = home.path().to_string_lossy().to_string();
+        let temp_path = temp.path().to_string_lossy().to_string();
+        let _env = set_env(&[
+            ("CANGJIE_HOME", Some(home_path.as_str())),
+            ("TEMP_DIR", Some(temp_path.as_str())),
+            ("ALLOWED_REPO_HOSTS", Some("fixture.test")),
+        ])
+        .await;
+
+        let options = CloneOptions {
+            depth: Some(0),
+            ..Default::default()
+        };
+        let outcome = load_or_analyze(&url, &options, None, None, &[], AnalyzeFlags::default())
+            .await
+            .unwrap();
+        assert_eq!(outcome.message, "no Cangjie source files found");
+        assert!(outcome.analysis_result.cjlint.is_empty());
+        assert!(fake_cjlint_calls(home.path()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn cjlint_version_is_reported_after_a_run() {
+        let home = tempfile::tempdir().unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        fake_redis().await;
+        fake_cjlint(home.path(), Some("[]"), 0);
+        let repo = fixture_package("version");
+        let home_path = home.path().to_string_lossy().to_string();
+        let temp_path = temp.path().to_string_lossy().to_string();
+        let _env = set_env(&[
+            ("CANGJIE_HOME", Some(home_path.as_str())),
+            ("TEMP_DIR", Some(temp_path.as_str())),
+            ("ALLOWED_REPO_HOSTS", Some("fixture.test")),
+        ])
+        .await;
+
+        let options = CloneOptions {
+            depth: Some(0),
+            ..Default::default()
+        };
+        let outcome = load_or_analyze(&repo, &options, None, None, &[], AnalyzeFlags::default())
+            .await
+            .unwrap();
+        let version = outcome.analysis_result.cjlint_version.unwrap();
+        assert!(!version.is_empty());
+    }
+
+    #[tokio::test]
+    async fn inline_rule_config_selects_the_rules_that_fire() {
+        let home = tempfile::tempdir().unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        fake_redis().await;
+        // 模拟的cjlint按行筛选规则，每个问题占一行
+        let finding = |rule: &str, level: &str| {
+            serde_json::json!({
+                "file": "{dir}/src/main.cj",
+                "line": 1,
+                "column": 1,
+                "endLine": 1,
+                "endColumn": 2,
+                "analyzerName": rule,
+                "description": "demo",
+                "defectLevel": level,
+                "defectType": "FMT",
+                "language": "Cangjie"
+            })
+        };
+        let report = format!(
+            "[\n{},\n{}\n]\n",
+            finding("G.FMT.01", "SUGGESTIONS"),
+            finding("P.ERR.02", "MANDATORY")
+        );
+        fake_cjlint(home.path(), Some(&report), 0);
+        let url = fixture_package("rules");
+        let home_path = home.path().to_string_lossy().to_string();
+        let temp_path = temp.path().to_string_lossy().to_string();
+        let _env = set_env(&[
+            ("CANGJIE_HOME", Some(home_path.as_str())),
+            ("TEMP_DIR", Some(temp_path.as_str())),
+            ("ALLOWED_REPO_HOSTS", Some("fixture.test")),
+        ])
+        .await;
+
+        let options = CloneOptions {
+            depth: Some(0),
+            ..Default::default()
+        };
+        let rules = |outcome: RefreshOutcome| -> Vec<String> {
+            let cjlint = outcome.analysis_result.cjlint;
+            cjlint.into_iter().map(|item| item.analyzer_name).collect()
+        };
+        let outcome = load_or_analyze(&url, &options, None, None, &[], AnalyzeFlags::default())
+            .await
+            .unwrap();
+        assert_eq!(rules(outcome), ["G.FMT.01", "P.ERR.02"]);
+
+        let inline = serde_json::json!({ "RuleList": ["P.ERR.02"] });
+        let rule_config = RuleConfig::from_request(Some(&inline), None).unwrap().unwrap();
+        let flags = AnalyzeFlags::default();
+        let outcome = load_or_analyze(&url, &options, None, Some(&rule_config), &[], flags)
+            .await
+            .unwrap();
+        assert!(!outcome.from_cache);
+        let applied = outcome.analysis_result.rule_config.clone().unwrap();
+        assert_eq!(applied.source, "inline");
+        assert_eq!(applied.sha256.len(), 64);
+        assert_eq!(rules(outcome), ["P.ERR.02"]);
+    }
+
+    #[tokio::test]
+    async fn snippets_show_the_reported_lines() {
+        let home = tempfile::tempdir().unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        fake_redis().await;
+        let report = r#"[
+            {"file": "{dir}/src/main.cj", "line": 2, "column": 5, "endLine": 3,
+             "endColumn": 14, "analyzerName": "G.FMT.01", "description": "demo",
+             "defectLevel": "SUGGESTIONS", "defectType": "FMT", "language": "Cangjie"},
+            {"file": "{dir}/src/main.cj", "line": 40, "column": 1, "endLine": 40,
+             "endColumn": 2, "analyzerName": "G.FMT.02", "description": "stale",
+             "defectLevel": "SUGGESTIONS", "defectType": "FMT", "language": "Cangjie"}
+        ]"#;
+        fake_cjlint(home.path(), Some(report), 0);
+        let (repo, url) = fixture_repo("snippets");
+        commit_file(&repo, "cjpm.toml", "[package]\nname = \"demo\"\n");
+        commit_file(&repo, "src/main.cj", "main() {\n    let x = 1\n    println(x)\n}\n");
+        let home_path = home.path().to_string_lossy().to_string();
+        let temp_path = temp.path().to_string_lossy().to_string();
+        let _env = set_env(&[
+            ("CANGJIE_HOME", Some(home_path.as_str())),
+            ("TEMP_DIR", Some(temp_path.as_str())),
+            ("ALLOWED_REPO_HOSTS", Some("fixture.test")),
+        ])
+        .await;
+
+        let options = CloneOptions {
+            depth: Some(0),
+            ..Default::default()
+        };
+        let outcome = load_or_analyze(&url, &options, None, None, &[], AnalyzeFlags::default())
+            .await
+            .unwrap();
+        assert!(outcome.analysis_result.cjlint.iter().all(|item| item.snippet.is_none()));
+
+        // 附带片段的结果单独缓存，不会读到上面不带片段的结果
+        let flags = AnalyzeFlags {
+            include_snippets: true,
+            ..Default::default()
+        };
+        let outcome = load_or_analyze(&url, &options, None, None, &[], flags).await.unwrap();
+        assert!(!outcome.from_cache);
+        let snippets: Vec<Option<String>> =
+            outcome.analysis_result.cjlint.into_iter().map(|item| item.snippet).collect();
+        // 行号超出文件范围的问题不附加片段
+        assert_eq!(snippets, [Some("    let x = 1\n    println(x)".to_string()), None]);
+    }
+
+    #[tokio::test]
+    async fn analyze_local_runs_cjlint_on_fixture_directory() {
+        let home = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        fake_cjlint(home.path(), Some(REPORT), 0);
+        let fixture = root.path().join("demo");
+        write_file(&fixture, "cjpm.toml", "[package]\nname = \"demo\"\n");
+        write_file(&fixture, "src/main.cj", "main() {\n    println(1)\n}\n");
+
+        let home_path = home.path().to_string_lossy().to_string();
+        let root_path = root.path().to_string_lossy().to_string();
+        let _env = set_env(&[
+            ("CANGJIE_HOME", Some(home_path.as_str())),
+            ("ALLOW_LOCAL_PATHS", Some("1")),
+            ("LOCAL_PATH_ROOT", Some(root_path.as_str())),
+        ])
+        .await;
+
+        assert!(validate_local_path("/etc").is_err());
+        let dir = validate_local_path(&fixture.to_string_lossy()).unwrap();
+        let outcome = analyze_local(&dir, None, None, &[], false).await.unwrap();
+
+        let analysis_result = outcome.analysis_result;
+        assert_eq!(analysis_result.package_name, "demo");
+        assert_eq!(analysis_result.files_analyzed, 1);
+        assert_eq!(analysis_result.cjlint.len(), 1);
+        assert_eq!(analysis_result.cjlint[0].file, "src/main.cj");
+        assert_eq!(fake_cjlint_calls(home.path()), [dir.to_string_lossy()]);
+    }
+
+    #[tokio::test]
+    async fn force_ignores_the_commit_cache_and_replaces_it() {
+        let home = tempfile::tempdir().unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        fake_redis().await;
+        fake_cjlint(home.path(), Some(REPORT), 0);
+        let url = fixture_package("forced");
+        let home_path = home.path().to_string_lossy().to_string();
+        let temp_path = temp.path().to_string_lossy().to_string();
+        let _env = set_env(&[
+            ("CANGJIE_HOME", Some(home_path.as_str())),
+            ("TEMP_DIR", Some(temp_path.as_str())),
+            ("ALLOWED_REPO_HOSTS", Some("fixture.test")),
+        ])
+        .await;
+
+        let options = CloneOptions {
+            depth: Some(0),
+            ..Default::default()
+        };
+        let run = |force: bool| {
+            let flags = AnalyzeFlags {
+                force,
+                ..Default::default()
+            };
+            let (url, options) = (url.clone(), options.clone());
+            async move {
+                load_or_analyze(&url, &options, None, None, &[], flags)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let outcome = run(false).await;
+        assert!(!outcome.from_cache);
+        assert_eq!(outcome.analysis_result.cjlint.len(), 1);
+        let outcome = run(false).await;
+        assert!(outcome.from_cache);
+        assert_eq!(fake_cjlint_calls(home.path()).len(), 1);
+
+        // 强制刷新时重新克隆和分析，新结果覆盖同一提交的缓存
+        fake_cjlint(home.path(), Some("[]"), 0);
+        let outcome = run(true).await;
+        assert!(!outcome.from_cache);
+        assert!(outcome.analysis_result.cjlint.is_empty());
+        assert_eq!(fake_cjlint_calls(home.path()).len(), 2);
+        let outcome = run(false).await;
+        assert!(outcome.from_cache);
+        assert!(outcome.analysis_result.cjlint.is_empty());
+    }
+}
@@ -0,0 +1,87 @@
+use crate::config::{
+    rate_limit_max_requests, rate_limit_per_ip, rate_limit_window_seconds, redis_optional,
+};
+use crate::error::RefreshError;
+use crate::storage::increment_rate_counter;
+use crate::utils::normalize_repo_key;
+use tracing::warn;
+use vercel_runtime::Request;
+
+/// 从x-forwarded-for请求头中读取客户端IP，取第一个地址
+pub fn client_ip(req: &Request) -> Option<String> {
+    req.headers()
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .map(|ip| ip.trim().to_string())
+        .filter(|ip| !ip.is_empty())
+}
+
+/// 检查仓库（以及开启时的客户端IP）在当前时间窗口内的刷新次数，超出时返回 RateLimited
+pub async fn check_rate_limit(repo: &str, client_ip: Option<&str>) -> Result<(), RefreshError> {
+    let max_requests = rate_limit_max_requests();
+    if max_requests == 0 {
+        return Ok(());
+    }
+
+    let mut keys = vec![format!("cjlint_ratelimit_repo_{}", normalize_repo_key(repo))];
+    if rate_limit_per_ip() {
+        if let Some(ip) = client_ip {
+            keys.push(format!("cjlint_ratelimit_ip_{}", ip));
+        }
+    }
+
+    let window_seconds = rate_limit_window_seconds();
+    for key in keys {
+        // REDIS_OPTIONAL 开启时无法计数则放行
+        let (count, ttl) = match increment_rate_counter(&key, window_seconds).await {
+            Ok(counter) => counter,
+            Err(RefreshError::RedisUnavailable(e)) if redis_optional() => {
+                warn!("Skipping rate limit because Redis is unavailable: {}", e);
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+        if count > max_requests {
+            warn!(count, retry_after = ttl, "Rate limit exceeded");
+            return Err(RefreshError::RateLimited { retry_after: ttl });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{fake_redis, set_env};
+
+    #[tokio::test]
+    async fn requests_over_the_limit_are_rejected() {
+        fake_redis().await;
+        let _env = set_env(&[
+            ("RATE_LIMIT_MAX_REQUESTS", Some("2")),
+            ("RATE_LIMIT_WINDOW_SECONDS", Some("60")),
+            ("RATE_LIMIT_PER_IP", Some("1")),
+        ])
+        .await;
+
+        let repo = "https://github.com/demo/rate_limit";
+        check_rate_limit(repo, Some("203.0.113.1")).await.unwrap();
+        check_rate_limit(repo, Some("203.0.113.2")).await.unwrap();
+        let error = check_rate_limit(repo, Some("203.0.113.3")).await.err().unwrap();
+        assert!(matches!(error, RefreshError::RateLimited { retry_after: 1..=60 }));
+        assert_eq!(error.status_code().as_u16(), 429);
+
+        // 同一IP刷新不同仓库时按IP单独计数
+        for name in ["a", "b"] {
+            let repo = format!("https://github.com/demo/rate_limit_ip_{}", name);
+            check_rate_limit(&repo, Some("198.51.100.1")).await.unwrap();
+        }
+        let repo = "https://github.com/demo/rate_limit_ip_c";
+        assert!(matches!(
+            check_rate_limit(repo, Some("198.51.100.1")).await,
+            Err(RefreshError::RateLimited { .. })
+        ));
+    }
+}
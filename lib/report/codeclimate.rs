@@ -0,0 +1,80 @@
+use serde_json::{json, Value};
+use crate::models::{AnalysisResultItem, DefectLevel};
+use super::fingerprint;
+
+/// 将分析结果转换为 CodeClimate 引擎规范中的问题列表
+///
+/// 强制问题归为 Bug Risk，建议问题归为 Style
+pub fn to_codeclimate(analysis_result: &[AnalysisResultItem]) -> Value {
+    let issues: Vec<Value> = analysis_result
+        .iter()
+        .map(|item| {
+            let (severity, category) = match item.defect_level {
+                DefectLevel::Mandatory => ("major", "Bug Risk"),
+                DefectLevel::Suggestions => ("minor", "Style"),
+            };
+            json!({
+                "type": "issue",
+                "check_name": item.analyzer_name,
+                "description": item.description,
+                "categories": [category],
+                "location": {
+                    "path": item.file,
+                    "positions": {
+                        "begin": { "line": item.line, "column": item.column },
+                        "end": { "line": item.end_line, "column": item.end_column },
+                    },
+                },
+                "fingerprint": fingerprint(item),
+                "severity": severity,
+            })
+        })
+        .collect();
+
+    Value::Array(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::gitlab::to_gitlab;
+    use crate::test_support::item;
+
+    #[test]
+    fn codeclimate_issue_has_categories_positions_and_fingerprint() {
+        let items = [
+            item("src/main.cj", 3, "G.ERR.02", DefectLevel::Mandatory),
+            item("src/main.cj", 7, "G.FMT.01", DefectLevel::Suggestions),
+        ];
+        let report = to_codeclimate(&items);
+
+        assert_eq!(
+            report[0],
+            json!({
+                "type": "issue",
+                "check_name": "G.ERR.02",
+                "description": "G.ERR.02 finding",
+                "categories": ["Bug Risk"],
+                "location": {
+                    "path": "src/main.cj",
+                    "positions": {
+                        "begin": { "line": 3, "column": 1 },
+                        "end": { "line": 3, "column": 10 },
+                    },
+                },
+                "fingerprint": fingerprint(&items[0]),
+                "severity": "major",
+            })
+        );
+        assert_eq!(report[1]["categories"], json!(["Style"]));
+        assert_eq!(report[1]["severity"], "minor");
+
+        // 与 GitLab 报告使用相同的指纹，两次生成的结果一致
+        let gitlab = to_gitlab(&items);
+        for index in 0..items.len() {
+            assert_eq!(report[index]["fingerprint"], gitlab[index]["fingerprint"]);
+        }
+        assert_eq!(to_codeclimate(&items), report);
+        assert_ne!(report[0]["fingerprint"], report[1]["fingerprint"]);
+    }
+}
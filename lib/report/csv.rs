@@ -0,0 +1,82 @@
+use crate::models::{AnalysisResultItem, DefectLevel};
+
+const CSV_HEADER: &str =
+    "file,line,column,endLine,endColumn,analyzerName,defectLevel,defectType,description";
+
+/// 将分析结果转换为 CSV，字段顺序与 CSV_HEADER 一致，使用 CRLF 换行以兼容 Excel
+pub fn to_csv(analysis_result: &[AnalysisResultItem]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push_str("\r\n");
+
+    for item in analysis_result {
+        let defect_level = match item.defect_level {
+            DefectLevel::Mandatory => "MANDATORY",
+            DefectLevel::Suggestions => "SUGGESTIONS",
+        };
+        let fields = [
+            escape_field(&item.file),
+            item.line.to_string(),
+            item.column.to_string(),
+            item.end_line.to_string(),
+            item.end_column.to_string(),
+            escape_field(&item.analyzer_name),
+            defect_level.to_string(),
+            escape_field(&item.defect_type),
+            escape_field(&item.description),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+
+    csv
+}
+
+/// 下载时使用的文件名，只保留包名中可以安全放入响应头的字符
+pub fn csv_filename(package_name: &str) -> String {
+    let name: String = package_name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => c,
+            _ => '_',
+        })
+        .collect();
+    format!("{}-cjlint.csv", name)
+}
+
+/// 包含逗号、引号或换行的字段用引号包裹，字段内的引号写两次
+fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::item;
+
+    #[test]
+    fn csv_quotes_fields_with_separators_quotes_and_newlines() {
+        let mut finding = item("src/a,b.cj", 3, "G.FMT.01", DefectLevel::Mandatory);
+        finding.description = "say \"hi\"\nthen leave".to_string();
+        let plain = item("src/main.cj", 7, "G.ERR.02", DefectLevel::Suggestions);
+
+        assert_eq!(
+            to_csv(&[finding, plain]),
+            format!(
+                "{}\r\n\
+                 \"src/a,b.cj\",3,1,3,10,G.FMT.01,MANDATORY,TYPE,\"say \"\"hi\"\"\nthen leave\"\r\n\
+                 src/main.cj,7,1,7,10,G.ERR.02,SUGGESTIONS,TYPE,G.ERR.02 finding\r\n",
+                CSV_HEADER
+            )
+        );
+    }
+
+    #[test]
+    fn csv_filename_replaces_unsafe_characters() {
+        assert_eq!(csv_filename("demo.pkg"), "demo.pkg-cjlint.csv");
+        assert_eq!(csv_filename("a b/\"c\""), "a_b__c_-cjlint.csv");
+    }
+}
@@ -0,0 +1,98 @@
+use serde_json::Value;
+use crate::analysis::parse_list;
+use crate::error::RefreshError;
+use crate::models::AnalysisResult;
+
+// AnalysisResultItem 序列化后的字段名
+const ITEM_FIELDS: &[&str] = &[
+    "file",
+    "absolutePath",
+    "line",
+    "column",
+    "endLine",
+    "endColumn",
+    "analyzerName",
+    "description",
+    "defectLevel",
+    "originalDefectLevel",
+    "defectType",
+    "language",
+    "package",
+    "count",
+    "snippet",
+];
+
+/// 解析以逗号分隔的问题字段列表，字段名与JSON输出中的一致，出现未知字段时返回参数错误
+pub fn parse_fields(raw: &str) -> Result<Vec<String>, RefreshError> {
+    let fields = parse_list(raw);
+    if fields.is_empty() {
+        return Err(RefreshError::InvalidParameter(
+            "fields must list at least one field".to_string(),
+        ));
+    }
+    if let Some(unknown) = fields.iter().find(|field| !ITEM_FIELDS.contains(&field.as_str())) {
+        return Err(RefreshError::InvalidParameter(format!(
+            "Unknown field: {}, expected any of {}",
+            unknown,
+            ITEM_FIELDS.join(", ")
+        )));
+    }
+    Ok(fields)
+}
+
+/// 将分析结果转换为JSON，问题只保留 `fields` 中列出的字段，其余部分保持不变
+///
+/// 可选字段在问题中没有值时仍然省略
+pub fn project_fields(
+    analysis_result: &AnalysisResult,
+    fields: &[String],
+) -> Result<Value, serde_json::Error> {
+    let mut value = serde_json::to_value(analysis_result)?;
+    if let Some(Value::Array(items)) = value.get_mut("cjlint") {
+        for item in items {
+            if let Value::Object(item) = item {
+                item.retain(|key, _| fields.contains(key));
+            }
+        }
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DefectLevel;
+    use crate::test_support::{item, result};
+    use serde_json::json;
+
+    #[test]
+    fn projection_keeps_only_requested_fields() {
+        let mut finding = item("src/main.cj", 3, "G.FMT.01", DefectLevel::Suggestions);
+        finding.package = Some("demo".to_string());
+        let analysis_result = result(vec![
+            finding,
+            item("src/lib.cj", 7, "G.ERR.02", DefectLevel::Mandatory),
+        ]);
+
+        let fields = parse_fields("file, line,package").unwrap();
+        let value = project_fields(&analysis_result, &fields).unwrap();
+        // 没有值的可选字段仍然省略
+        assert_eq!(
+            value["cjlint"],
+            json!([
+                { "file": "src/main.cj", "line": 3, "package": "demo" },
+                { "file": "src/lib.cj", "line": 7 },
+            ])
+        );
+        assert_eq!(value["package_name"], "demo");
+    }
+
+    #[test]
+    fn unknown_or_empty_fields_are_rejected() {
+        assert!(matches!(
+            parse_fields("file,lines"),
+            Err(RefreshError::InvalidParameter(message)) if message.starts_with("Unknown field: lines")
+        ));
+        assert!(matches!(parse_fields(" , "), Err(RefreshError::InvalidParameter(_))));
+    }
+}
@@ -0,0 +1,61 @@
+use std::fmt::Write;
+use crate::models::{AnalysisResultItem, DefectLevel};
+
+/// 将分析结果转换为 GitHub Actions 的工作流命令，每行一个注解
+///
+/// 强制问题输出为 `::error`，建议问题输出为 `::warning`
+pub fn to_github_annotations(analysis_result: &[AnalysisResultItem]) -> String {
+    let mut output = String::new();
+    for item in analysis_result {
+        let command = match item.defect_level {
+            DefectLevel::Mandatory => "error",
+            DefectLevel::Suggestions => "warning",
+        };
+        let _ = writeln!(
+            output,
+            "::{} file={},line={},col={},endLine={},endColumn={},title={}::{}",
+            command,
+            escape_property(&item.file),
+            item.line,
+            item.column,
+            item.end_line,
+            item.end_column,
+            escape_property(&item.analyzer_name),
+            escape_data(&item.description)
+        );
+    }
+    output
+}
+
+/// 转义命令消息中的特殊字符
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// 转义命令属性中的特殊字符，属性中还需要转义分隔符
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::item;
+
+    #[test]
+    fn annotations_escape_properties_and_messages() {
+        let mut mandatory = item("src/a,b:c%.cj", 3, "G.FMT:01", DefectLevel::Mandatory);
+        mandatory.description = "50% done\r\nnext: a, b".to_string();
+        let suggestion = item("src/main.cj", 7, "G.ERR.02", DefectLevel::Suggestions);
+
+        assert_eq!(
+            to_github_annotations(&[mandatory, suggestion]),
+            "::error file=src/a%2Cb%3Ac%25.cj,line=3,col=1,endLine=3,endColumn=10,\
+             title=G.FMT%3A01::50%25 done%0D%0Anext: a, b\n\
+             ::warning file=src/main.cj,line=7,col=1,endLine=7,endColumn=10,\
+             title=G.ERR.02::G.ERR.02 finding\n"
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use serde_json::{json, Value};
+use crate::models::{AnalysisResultItem, DefectLevel};
+use super::fingerprint;
+
+/// 将分析结果转换为 GitLab Code Quality 报告
+pub fn to_gitlab(analysis_result: &[AnalysisResultItem]) -> Value {
+    let issues: Vec<Value> = analysis_result
+        .iter()
+        .map(|item| {
+            json!({
+                "description": item.description,
+                "check_name": item.analyzer_name,
+                "fingerprint": fingerprint(item),
+                "severity": match item.defect_level {
+                    DefectLevel::Mandatory => "major",
+                    DefectLevel::Suggestions => "minor",
+                },
+                "location": {
+                    "path": item.file,
+                    "lines": { "begin": item.line, "end": item.end_line },
+                },
+            })
+        })
+        .collect();
+
+    Value::Array(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::item;
+
+    #[test]
+    fn gitlab_issue_has_stable_fingerprint() {
+        let finding = item("src/main.cj", 3, "G.FMT.01", DefectLevel::Suggestions);
+        let report = to_gitlab(std::slice::from_ref(&finding));
+        assert_eq!(
+            report,
+            json!([{
+                "description": "G.FMT.01 finding",
+                "check_name": "G.FMT.01",
+                "fingerprint": fingerprint(&finding),
+                "severity": "minor",
+                "location": {
+                    "path": "src/main.cj",
+                    "lines": { "begin": 3, "end": 3 },
+                },
+            }])
+        );
+
+        // 同一问题在另一次分析中的指纹不变，不影响位置和内容的字段不参与计算
+        let mut rerun = item("src/main.cj", 3, "G.FMT.01", DefectLevel::Suggestions);
+        rerun.package = Some("demo".to_string());
+        rerun.count = Some(2);
+        assert_eq!(to_gitlab(&[rerun])[0]["fingerprint"], report[0]["fingerprint"]);
+
+        let mut moved = item("src/main.cj", 3, "G.FMT.01", DefectLevel::Suggestions);
+        moved.line = 4;
+        assert_ne!(to_gitlab(&[moved])[0]["fingerprint"], report[0]["fingerprint"]);
+    }
+}
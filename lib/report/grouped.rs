@@ -0,0 +1,53 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+use crate::models::AnalysisResultItem;
+
+/// 按文件分组分析结果，组内按行列排序，并省略每一项中的file字段
+pub fn group_by_file(
+    analysis_result: &[AnalysisResultItem],
+) -> Result<BTreeMap<String, Vec<Value>>, serde_json::Error> {
+    let mut groups: BTreeMap<&str, Vec<&AnalysisResultItem>> = BTreeMap::new();
+    for item in analysis_result {
+        groups.entry(item.file.as_str()).or_default().push(item);
+    }
+
+    groups
+        .into_iter()
+        .map(|(file, mut items)| {
+            items.sort_by_key(|item| (item.line, item.column));
+            let items = items
+                .into_iter()
+                .map(|item| {
+                    let mut value = serde_json::to_value(item)?;
+                    if let Value::Object(fields) = &mut value {
+                        fields.remove("file");
+                    }
+                    Ok(value)
+                })
+                .collect::<Result<Vec<_>, serde_json::Error>>()?;
+            Ok((file.to_string(), items))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DefectLevel;
+    use crate::test_support::item;
+
+    #[test]
+    fn findings_are_grouped_and_sorted_without_file() {
+        let items = [
+            item("src/b.cj", 9, "G.FMT.01", DefectLevel::Suggestions),
+            item("src/a.cj", 7, "G.ERR.02", DefectLevel::Mandatory),
+            item("src/b.cj", 2, "G.ERR.02", DefectLevel::Mandatory),
+        ];
+
+        let groups = group_by_file(&items).unwrap();
+        assert_eq!(groups.keys().collect::<Vec<_>>(), ["src/a.cj", "src/b.cj"]);
+        let lines: Vec<&Value> = groups["src/b.cj"].iter().map(|item| &item["line"]).collect();
+        assert_eq!(lines, [2, 9]);
+        assert!(groups.values().flatten().all(|item| item.get("file").is_none()));
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use crate::models::{AnalysisResultItem, DefectLevel};
+
+/// 将分析结果转换为 JUnit XML，每个文件对应一个 testsuite，每个问题对应一个 testcase
+///
+/// 强制问题记为 failure，建议问题默认记为 skipped，`suggestions_as_failures` 为真时同样记为 failure
+pub fn to_junit(analysis_result: &[AnalysisResultItem], suggestions_as_failures: bool) -> String {
+    let mut files: BTreeMap<&str, Vec<&AnalysisResultItem>> = BTreeMap::new();
+    for item in analysis_result {
+        files.entry(item.file.as_str()).or_default().push(item);
+    }
+
+    let is_failure = |item: &AnalysisResultItem| {
+        item.defect_level == DefectLevel::Mandatory || suggestions_as_failures
+    };
+    let total_failures = analysis_result.iter().filter(|item| is_failure(item)).count();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"cjlint\" tests=\"{}\" failures=\"{}\">",
+        analysis_result.len(),
+        total_failures
+    );
+
+    for (file, items) in files {
+        let failures = items.iter().filter(|item| is_failure(item)).count();
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\">",
+            escape_xml(file),
+            items.len(),
+            failures,
+            items.len() - failures
+        );
+
+        for item in items {
+            let _ = writeln!(
+                xml,
+                "    <testcase classname=\"{}\" name=\"{}:{}:{}\">",
+                escape_xml(&item.analyzer_name),
+                escape_xml(file),
+                item.line,
+                item.column
+            );
+            if is_failure(item) {
+                let _ = writeln!(
+                    xml,
+                    "      <failure message=\"{}\" type=\"{}\">{}</failure>",
+                    escape_xml(&item.description),
+                    escape_xml(&item.defect_type),
+                    escape_xml(&item.description)
+                );
+            } else {
+                let _ = writeln!(
+                    xml,
+                    "      <skipped message=\"{}\"/>",
+                    escape_xml(&item.description)
+                );
+            }
+            xml.push_str("    </testcase>\n");
+        }
+
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+/// 转义 XML 文本和属性中的特殊字符
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::item;
+
+    fn items() -> Vec<AnalysisResultItem> {
+        let mut mandatory = item("src/a.cj", 2, "G.ERR.02", DefectLevel::Mandatory);
+        mandatory.description = "use \"x\" & <y>".to_string();
+        vec![
+            mandatory,
+            item("src/a.cj", 5, "G.FMT.01", DefectLevel::Suggestions),
+            item("src/b.cj", 1, "G.FMT.01", DefectLevel::Suggestions),
+        ]
+    }
+
+    #[test]
+    fn junit_groups_files_and_skips_suggestions() {
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="cjlint" tests="3" failures="1">
+  <testsuite name="src/a.cj" tests="2" failures="1" skipped="1">
+    <testcase classname="G.ERR.02" name="src/a.cj:2:1">
+      <failure message="use &quot;x&quot; &amp; &lt;y&gt;" type="TYPE">use &quot;x&quot; &amp; &lt;y&gt;</failure>
+    </testcase>
+    <testcase classname="G.FMT.01" name="src/a.cj:5:1">
+      <skipped message="G.FMT.01 finding"/>
+    </testcase>
+  </testsuite>
+  <testsuite name="src/b.cj" tests="1" failures="0" skipped="1">
+    <testcase classname="G.FMT.01" name="src/b.cj:1:1">
+      <skipped message="G.FMT.01 finding"/>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#;
+        assert_eq!(to_junit(&items(), false), expected);
+    }
+
+    #[test]
+    fn junit_can_fail_on_suggestions() {
+        let xml = to_junit(&items(), true);
+        assert!(xml.contains("<testsuites name=\"cjlint\" tests=\"3\" failures=\"3\">"));
+        assert!(xml.contains("<testsuite name=\"src/b.cj\" tests=\"1\" failures=\"1\" skipped=\"0\">"));
+        assert!(!xml.contains("<skipped"));
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use url::Url;
+use crate::models::{AnalysisResult, AnalysisResultItem, DefectLevel};
+
+// 表格中最多列出的问题数量，避免超出 issue 和 PR 评论的长度限制
+const MAX_MARKDOWN_ROWS: usize = 500;
+
+/// 生成适合粘贴到 issue 或 PR 评论中的 Markdown 报告
+///
+/// 头部为包名、提交和问题统计，之后按文件分组列出问题；
+/// 仓库托管在已知平台时，行号链接到对应提交中的源码
+pub fn to_markdown(analysis_result: &AnalysisResult, repo: Option<&str>) -> String {
+    let summary = &analysis_result.summary;
+    let mut output = String::new();
+    let _ = writeln!(output, "# cjlint report: {}", escape_cell(&analysis_result.package_name));
+    let _ = writeln!(output);
+    let _ = writeln!(output, "- Commit: `{}`", analysis_result.commit);
+    let _ = writeln!(
+        output,
+        "- Findings: {} (mandatory: {}, suggestions: {})",
+        summary.total, summary.mandatory, summary.suggestions
+    );
+
+    let items = &analysis_result.cjlint;
+    if items.is_empty() {
+        let _ = writeln!(output);
+        let _ = writeln!(output, "No findings.");
+        return output;
+    }
+
+    let source_base = repo.and_then(|repo| source_base_url(repo, &analysis_result.commit));
+    let mut groups: BTreeMap<&str, Vec<&AnalysisResultItem>> = BTreeMap::new();
+    for item in items.iter().take(MAX_MARKDOWN_ROWS) {
+        groups.entry(item.file.as_str()).or_default().push(item);
+    }
+
+    for (file, items) in groups {
+        let _ = writeln!(output);
+        let _ = writeln!(output, "## `{}`", file);
+        let _ = writeln!(output);
+        let _ = writeln!(output, "| Line | Level | Analyzer | Description |");
+        let _ = writeln!(output, "| ---: | --- | --- | --- |");
+        for item in items {
+            let line = match &source_base {
+                Some(base) => format!("[{}]({})", item.line, source_link(base, file, item.line)),
+                None => item.line.to_string(),
+            };
+            let level = match item.defect_level {
+                DefectLevel::Mandatory => "mandatory",
+                DefectLevel::Suggestions => "suggestion",
+            };
+            let _ = writeln!(
+                output,
+                "| {} | {} | {} | {} |",
+                line,
+                level,
+                escape_cell(&item.analyzer_name),
+                escape_cell(&item.description)
+            );
+        }
+    }
+
+    if items.len() > MAX_MARKDOWN_ROWS {
+        let _ = writeln!(output);
+        let _ = writeln!(
+            output,
+            "_Showing the first {} of {} findings._",
+            MAX_MARKDOWN_ROWS,
+            items.len()
+        );
+    }
+
+    output
+}
+
+/// 已知托管平台上该提交的源码地址前缀，其他平台返回 None
+fn source_base_url(repo: &str, commit: &str) -> Option<Url> {
+    let url = Url::parse(repo).ok()?;
+    let host = url.host_str()?;
+    let blob = match host {
+        "github.com" | "gitee.com" | "gitcode.com" => "blob",
+        "gitlab.com" => "-/blob",
+        _ => return None,
+    };
+    let path = url.path().trim_end_matches('/').trim_end_matches(".git");
+    Url::parse(&format!("https://{}{}/{}/{}", host, path, blob, commit)).ok()
+}
+
+/// 文件中某一行的源码地址，路径逐段百分号编码，括号同样编码以免提前结束 Markdown 链接
+fn source_link(base: &Url, file: &str, line: i32) -> String {
+    let mut url = base.clone();
+    if let Ok(mut segments) = url.path_segments_mut() {
+        segments.extend(file.split('/'));
+    }
+    url.set_fragment(Some(&format!("L{}", line)));
+    url.as_str().replace('(', "%28").replace(')', "%29")
+}
+
+/// 转义表格单元格中的竖线，并把换行替换为空格
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{item, result};
+
+    #[test]
+    fn markdown_links_encode_file_paths() {
+        let analysis_result = result(vec![item(
+            "src/my file (1)#.cj",
+            3,
+            "G.FMT.01",
+            DefectLevel::Suggestions,
+        )]);
+
+        let report = to_markdown(&analysis_result, Some("https://github.com/demo/repo.git"));
+        assert!(report.contains(
+            "| [3](https://github.com/demo/repo/blob/0123456789abcdef0123456789abcdef01234567\
+             /src/my%20file%20%281%29%23.cj#L3) | suggestion | G.FMT.01 | G.FMT.01 finding |"
+        ));
+
+        // 未知平台不生成链接
+        let report = to_markdown(&analysis_result, Some("https://example.com/demo/repo"));
+        assert!(report.contains("| 3 | suggestion | G.FMT.01 | G.FMT.01 finding |"));
+    }
+
+    #[test]
+    fn markdown_caps_listed_findings() {
+        let items = (1..=MAX_MARKDOWN_ROWS as i32 + 2)
+            .map(|line| item("src/main.cj", line, "G.FMT.01", DefectLevel::Suggestions))
+            .collect();
+        let report = to_markdown(&result(items), None);
+
+        let rows = report.lines().filter(|line| line.contains("| suggestion |")).count();
+        assert_eq!(rows, MAX_MARKDOWN_ROWS);
+        assert!(report.ends_with(&format!(
+            "_Showing the first {} of {} findings._\n",
+            MAX_MARKDOWN_ROWS,
+            MAX_MARKDOWN_ROWS + 2
+        )));
+    }
+
+    #[test]
+    fn markdown_reports_no_findings() {
+        let report = to_markdown(&result(Vec::new()), None);
+        assert!(report.starts_with("# cjlint report: demo\n"));
+        assert!(report.ends_with("No findings.\n"));
+    }
+}
@@ -0,0 +1,80 @@
+use std::str::FromStr;
+use sha2::{Digest, Sha256};
+use crate::error::RefreshError;
+use crate::models::AnalysisResultItem;
+
+pub mod codeclimate;
+pub mod csv;
+pub mod fields;
+pub mod github;
+pub mod gitlab;
+pub mod grouped;
+pub mod junit;
+pub mod markdown;
+pub mod nested;
+pub mod sarif;
+
+// 分析结果的输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Sarif,
+    Junit,
+    Gitlab,
+    GithubAnnotations,
+    Csv,
+    CodeClimate,
+    Markdown,
+}
+
+impl OutputFormat {
+    /// 输出格式对应的Content-Type
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            OutputFormat::Json => "application/json",
+            OutputFormat::Sarif => "application/sarif+json",
+            OutputFormat::Junit => "application/xml",
+            OutputFormat::Gitlab => "application/json",
+            OutputFormat::GithubAnnotations => "text/plain; charset=utf-8",
+            OutputFormat::Csv => "text/csv; charset=utf-8",
+            OutputFormat::CodeClimate => "application/json",
+            OutputFormat::Markdown => "text/markdown; charset=utf-8",
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = RefreshError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(OutputFormat::Json),
+            "sarif" => Ok(OutputFormat::Sarif),
+            "junit" => Ok(OutputFormat::Junit),
+            "gitlab" => Ok(OutputFormat::Gitlab),
+            "github-annotations" => Ok(OutputFormat::GithubAnnotations),
+            "csv" => Ok(OutputFormat::Csv),
+            "codeclimate" => Ok(OutputFormat::CodeClimate),
+            "markdown" => Ok(OutputFormat::Markdown),
+            _ => Err(RefreshError::InvalidParameter(format!("Unsupported format: {}", s))),
+        }
+    }
+}
+
+/// 问题的稳定指纹，GitLab 和 CodeClimate 据此对比前后两次分析中新增和修复的问题
+pub(crate) fn fingerprint(item: &AnalysisResultItem) -> String {
+    let mut hasher = Sha256::new();
+    for part in [
+        item.analyzer_name.as_str(),
+        item.file.as_str(),
+        &item.line.to_string(),
+        &item.column.to_string(),
+        item.description.as_str(),
+    ] {
+        hasher.update(part.as_bytes());
+        // 分隔符避免不同字段拼接后产生相同的输入
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
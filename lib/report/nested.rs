@@ -0,0 +1,177 @@
+use std::str::FromStr;
+use serde::{Serialize, Serializer};
+use serde_json::{Map, Value};
+use crate::error::RefreshError;
+use crate::models::{AnalysisResult, AnalysisResultItem, DefectLevel};
+
+// JSON输出中问题位置的表示方式
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ItemShape {
+    // 扁平的 line、column、endLine、endColumn 字段
+    #[default]
+    Flat,
+    // `location: {start: {line, column}, end: {line, column}}`
+    Nested,
+}
+
+impl FromStr for ItemShape {
+    type Err = RefreshError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flat" => Ok(ItemShape::Flat),
+            "nested" => Ok(ItemShape::Nested),
+            _ => Err(RefreshError::InvalidParameter(format!(
+                "Invalid shape: {}, expected one of flat, nested",
+                s
+            ))),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Position {
+    line: i32,
+    column: i32,
+}
+
+#[derive(Serialize)]
+struct Location {
+    start: Position,
+    end: Position,
+}
+
+// 与 AnalysisResultItem 的字段一一对应，只有位置字段改为嵌套对象
+#[derive(Serialize)]
+struct NestedItemRepr<'a> {
+    file: &'a str,
+    #[serde(rename = "absolutePath", skip_serializing_if = "Option::is_none")]
+    absolute_path: Option<&'a str>,
+    location: Location,
+    #[serde(rename = "analyzerName")]
+    analyzer_name: &'a str,
+    description: &'a str,
+    #[serde(rename = "defectLevel")]
+    defect_level: DefectLevel,
+    #[serde(rename = "originalDefectLevel", skip_serializing_if = "Option::is_none")]
+    original_defect_level: Option<DefectLevel>,
+    #[serde(rename = "defectType")]
+    defect_type: &'a str,
+    language: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    package: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snippet: Option<&'a str>,
+}
+
+/// 以嵌套位置对象的形式序列化问题，只借用原有数据
+pub struct NestedItem<'a>(pub &'a AnalysisResultItem);
+
+impl Serialize for NestedItem<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let item = self.0;
+        NestedItemRepr {
+            file: &item.file,
+            absolute_path: item.absolute_path.as_deref(),
+            location: Location {
+                start: Position {
+                    line: item.line,
+                    column: item.column,
+                },
+                end: Position {
+                    line: item.end_line,
+                    column: item.end_column,
+                },
+            },
+            analyzer_name: &item.analyzer_name,
+            description: &item.description,
+            defect_level: item.defect_level,
+            original_defect_level: item.original_defect_level,
+            defect_type: &item.defect_type,
+            language: &item.language,
+            package: item.package.as_deref(),
+            count: item.count,
+            snippet: item.snippet.as_deref(),
+        }
+        .serialize(serializer)
+    }
+}
+
+// 问题使用嵌套位置的分析结果，其余字段与 AnalysisResult 相同
+#[derive(Serialize)]
+pub struct NestedResult<'a> {
+    pub cjlint: Vec<NestedItem<'a>>,
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+}
+
+/// 构造嵌套形式的分析结果，`items` 为从 `analysis_result` 中取出的问题列表
+///
+/// 只有问题以外的字段会转换为 JSON 值，问题本身在序列化时直接借用
+pub fn to_nested<'a>(
+    analysis_result: &AnalysisResult,
+    items: &'a [AnalysisResultItem],
+) -> Result<NestedResult<'a>, serde_json::Error> {
+    let mut rest = match serde_json::to_value(analysis_result)? {
+        Value::Object(rest) => rest,
+        _ => Map::new(),
+    };
+    rest.remove("cjlint");
+
+    Ok(NestedResult {
+        cjlint: items.iter().map(NestedItem).collect(),
+        rest,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{item, result};
+    use serde_json::json;
+
+    #[test]
+    fn nested_shape_moves_positions_into_location() {
+        let mut finding = item("src/main.cj", 3, "G.FMT.01", DefectLevel::Suggestions);
+        finding.end_line = 4;
+        finding.package = Some("demo".to_string());
+        let analysis_result = result(vec![finding]);
+
+        let nested = to_nested(&analysis_result, &analysis_result.cjlint).unwrap();
+        let mut value = serde_json::to_value(&nested).unwrap();
+        assert_eq!(
+            value["cjlint"],
+            json!([{
+                "file": "src/main.cj",
+                "location": {
+                    "start": { "line": 3, "column": 1 },
+                    "end": { "line": 4, "column": 10 },
+                },
+                "analyzerName": "G.FMT.01",
+                "description": "G.FMT.01 finding",
+                "defectLevel": "SUGGESTIONS",
+                "defectType": "TYPE",
+                "language": "Cangjie",
+                "package": "demo",
+            }])
+        );
+
+        // 问题以外的字段与扁平形式相同
+        let mut flat = serde_json::to_value(&analysis_result).unwrap();
+        flat.as_object_mut().unwrap().remove("cjlint");
+        value.as_object_mut().unwrap().remove("cjlint");
+        assert_eq!(value, flat);
+    }
+
+    #[test]
+    fn shape_parses_known_values() {
+        assert_eq!("flat".parse::<ItemShape>().unwrap(), ItemShape::Flat);
+        assert_eq!("nested".parse::<ItemShape>().unwrap(), ItemShape::Nested);
+        assert!(matches!(
+            "tree".parse::<ItemShape>(),
+            Err(RefreshError::InvalidParameter(_))
+        ));
+    }
+}
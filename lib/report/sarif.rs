@@ -0,0 +1,111 @@
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use crate::models::{AnalysisResultItem, DefectLevel};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// 将分析结果转换为 SARIF 2.1.0 文档
+pub fn to_sarif(analysis_result: &[AnalysisResultItem]) -> Value {
+    let rules: Vec<Value> = analysis_result
+        .iter()
+        .map(|item| item.analyzer_name.as_str())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|analyzer_name| json!({ "id": analyzer_name }))
+        .collect();
+
+    let results: Vec<Value> = analysis_result
+        .iter()
+        .map(|item| {
+            json!({
+                "ruleId": item.analyzer_name,
+                "level": match item.defect_level {
+                    DefectLevel::Mandatory => "error",
+                    DefectLevel::Suggestions => "warning",
+                },
+                "message": { "text": item.description },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": item.file },
+                        "region": {
+                            "startLine": item.line,
+                            "startColumn": item.column,
+                            "endLine": item.end_line,
+                            "endColumn": item.end_column,
+                        },
+                    },
+                }],
+                "properties": { "defectType": item.defect_type },
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "cjlint",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::item;
+
+    #[test]
+    fn sarif_lists_rules_once_and_maps_levels() {
+        let items = [
+            item("src/main.cj", 3, "G.FMT.01", DefectLevel::Suggestions),
+            item("src/lib.cj", 7, "G.ERR.02", DefectLevel::Mandatory),
+            item("src/lib.cj", 9, "G.FMT.01", DefectLevel::Suggestions),
+        ];
+
+        let result = |rule: &str, level: &str, file: &str, line: i32| {
+            json!({
+                "ruleId": rule,
+                "level": level,
+                "message": { "text": format!("{} finding", rule) },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": file },
+                        "region": {
+                            "startLine": line,
+                            "startColumn": 1,
+                            "endLine": line,
+                            "endColumn": 10,
+                        },
+                    },
+                }],
+                "properties": { "defectType": "TYPE" },
+            })
+        };
+        assert_eq!(
+            to_sarif(&items),
+            json!({
+                "$schema": SARIF_SCHEMA,
+                "version": "2.1.0",
+                "runs": [{
+                    "tool": {
+                        "driver": {
+                            "name": "cjlint",
+                            "rules": [{ "id": "G.ERR.02" }, { "id": "G.FMT.01" }],
+                        },
+                    },
+                    "results": [
+                        result("G.FMT.01", "warning", "src/main.cj", 3),
+                        result("G.ERR.02", "error", "src/lib.cj", 7),
+                        result("G.FMT.01", "warning", "src/lib.cj", 9),
+                    ],
+                }],
+            })
+        );
+    }
+}
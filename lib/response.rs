@@ -0,0 +1,265 @@
+use crate::config::cors_allowed_origins;
+use crate::error::RefreshError;
+use crate::models::{AnalysisTimings, ApiResponse};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use http::header::{
+    ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
+    ACCESS_CONTROL_EXPOSE_HEADERS, ACCESS_CONTROL_MAX_AGE, ACCEPT_ENCODING, CACHE_CONTROL,
+    CONTENT_ENCODING, ETAG, IF_NONE_MATCH, ORIGIN, RETRY_AFTER, VARY,
+};
+use http::{HeaderName, HeaderValue};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use vercel_runtime::{Body, Error, Request, Response, StatusCode};
+
+/// 构造统一格式的JSON响应
+pub fn create_response<T: Serialize>(
+    status_code: StatusCode,
+    success: bool,
+    message: Option<&str>,
+    data: Option<T>,
+    error: Option<&str>,
+) -> Result<Response<Body>, Error> {
+    let response = ApiResponse {
+        success,
+        message: message.map(String::from),
+        data,
+        error: error.map(String::from),
+    };
+
+    let body = serde_json::to_string(&response)
+        .map_err(|e| Error::from(format!("Failed to serialize response: {}", e)))?;
+
+    Ok(Response::builder()
+        .status(status_code)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body))?)
+}
+
+/// 根据错误类型构造对应状态码的失败响应
+pub fn create_error_response(error: &RefreshError) -> Result<Response<Body>, Error> {
+    let mut response = create_response::<()>(
+        error.status_code(),
+        false,
+        None,
+        None,
+        Some(&error.to_string()),
+    )?;
+
+    if let Some(retry_after) = error.retry_after() {
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from(retry_after));
+    }
+
+    Ok(response)
+}
+
+/// 构造指定Content-Type的原始响应
+pub fn create_raw_response(
+    status_code: StatusCode,
+    content_type: &str,
+    body: String,
+) -> Result<Response<Body>, Error> {
+    Ok(Response::builder()
+        .status(status_code)
+        .header("Content-Type", content_type)
+        .body(Body::from(body))?)
+}
+
+// 带ETag的响应每次使用前都需要向服务端确认
+const REVALIDATE_CACHE_CONTROL: &str = "no-cache";
+
+/// 根据若干内容片段计算强ETag
+pub fn compute_etag(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    format!("\"{:x}\"", hasher.finalize())
+}
+
+/// 请求的If-None-Match是否与ETag匹配，支持多个ETag、弱ETag和 `*`
+pub fn etag_matches(req: &Request, etag: &str) -> bool {
+    req.headers()
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+/// 为响应添加ETag和要求重新验证的Cache-Control
+pub fn with_etag(mut response: Response<Body>, etag: &str) -> Result<Response<Body>, Error> {
+    let headers = response.headers_mut();
+    headers.insert(ETAG, HeaderValue::from_str(etag)?);
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static(REVALIDATE_CACHE_CONTROL));
+    Ok(response)
+}
+
+// http 库没有提供以下响应头的常量
+const SERVER_TIMING: HeaderName = HeaderName::from_static("server-timing");
+const TIMING_ALLOW_ORIGIN: HeaderName = HeaderName::from_static("timing-allow-origin");
+
+/// 生成 `Server-Timing` 头的值，浏览器开发者工具会直接展示各阶段耗时
+pub fn server_timing_value(timings: &AnalysisTimings) -> String {
+    format!(
+        "clone;dur={}, lint;dur={}, total;dur={}",
+        timings.clone_ms, timings.lint_ms, timings.total_ms
+    )
+}
+
+/// 为响应添加各阶段耗时的 `Server-Timing` 头
+pub fn with_server_timing(
+    mut response: Response<Body>,
+    timings: &AnalysisTimings,
+) -> Response<Body> {
+    if let Ok(value) = HeaderValue::from_str(&server_timing_value(timings)) {
+        response.headers_mut().insert(SERVER_TIMING, value);
+    }
+    response
+}
+
+/// 构造内容未变化时的304响应
+pub fn create_not_modified_response(etag: &str) -> Result<Response<Body>, Error> {
+    let response = Response::builder()
+        .status(StatusCode::NOT_MODIFIED)
+        .body(Body::Empty)?;
+    with_etag(response, etag)
+}
+
+/// 请求的Accept-Encoding是否接受gzip
+fn accepts_gzip(req: &Request) -> bool {
+    req.headers()
+        .get_all(ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|encoding| {
+            let mut parts = encoding.split(';').map(str::trim);
+            let name = parts.next().unwrap_or_default();
+            // q=0 表示明确拒绝该编码
+            let rejected = parts.any(|param| {
+                param
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .is_some_and(|q| q == 0.0)
+            });
+            (name.eq_ignore_ascii_case("gzip") || name == "*") && !rejected
+        })
+}
+
+/// 调用方接受gzip时压缩响应体
+pub fn compress_response(req: &Request, response: Response<Body>) -> Result<Response<Body>, Error> {
+    if !accepts_gzip(req) {
+        return Ok(response);
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes: &[u8] = match &body {
+        Body::Empty => return Ok(Response::from_parts(parts, body)),
+        Body::Text(text) => text.as_bytes(),
+        Body::Binary(binary) => binary,
+    };
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(bytes)?;
+    let compressed = encoder.finish()?;
+
+    parts
+        .headers
+        .insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+    parts
+        .headers
+        .append(VARY, HeaderValue::from_static("Accept-Encoding"));
+
+    Ok(Response::from_parts(parts, Body::Binary(compressed)))
+}
+
+// 跨域请求允许的方法和请求头
+const CORS_ALLOWED_METHODS: &str = "GET, POST, OPTIONS";
+const CORS_ALLOWED_HEADERS: &str = "Authorization, Content-Type, If-None-Match";
+// 允许浏览器读取的响应头，限流时需要读取 Retry-After，轮询时需要读取 ETag
+const CORS_EXPOSED_HEADERS: &str = "Retry-After, ETag";
+// 预检结果的缓存时间（秒）
+const CORS_MAX_AGE_SECONDS: u64 = 24 * 60 * 60;
+
+/// 请求来源在白名单内时返回应写入 Access-Control-Allow-Origin 的值
+fn allowed_origin(req: &Request) -> Option<HeaderValue> {
+    let allowed_origins = cors_allowed_origins();
+    if allowed_origins.iter().any(|origin| origin == "*") {
+        return Some(HeaderValue::from_static("*"));
+    }
+
+    let origin = req.headers().get(ORIGIN)?;
+    let origin_str = origin.to_str().ok()?.trim_end_matches('/');
+    allowed_origins
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(origin_str))
+        .then(|| origin.clone())
+}
+
+/// 为响应添加CORS响应头，来源不在白名单内时保持原样
+pub fn apply_cors(req: &Request, mut response: Response<Body>) -> Response<Body> {
+    if let Some(origin) = allowed_origin(req) {
+        let headers = response.headers_mut();
+        if origin != "*" {
+            headers.append(VARY, HeaderValue::from_static("Origin"));
+        }
+        // 跨域时浏览器只有在 Timing-Allow-Origin 允许时才展示 Server-Timing
+        headers.insert(TIMING_ALLOW_ORIGIN, origin.clone());
+        headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        headers.insert(
+            ACCESS_CONTROL_EXPOSE_HEADERS,
+            HeaderValue::from_static(CORS_EXPOSED_HEADERS),
+        );
+    }
+
+    response
+}
+
+/// 构造OPTIONS预检请求的204响应
+pub fn create_preflight_response(req: &Request) -> Result<Response<Body>, Error> {
+    let response = Response::builder()
+        .status(StatusCode::NO_CONTENT)
+        .header(ACCESS_CONTROL_ALLOW_METHODS, CORS_ALLOWED_METHODS)
+        .header(ACCESS_CONTROL_ALLOW_HEADERS, CORS_ALLOWED_HEADERS)
+        .header(ACCESS_CONTROL_MAX_AGE, CORS_MAX_AGE_SECONDS)
+        .body(Body::Empty)?;
+
+    Ok(apply_cors(req, response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::set_env;
+
+    #[tokio::test]
+    async fn server_timing_is_exposed_to_allowed_origins() {
+        let _env = set_env(&[("CORS_ALLOWED_ORIGINS", Some("https://app.example"))]).await;
+        let timings = AnalysisTimings {
+            clone_ms: 120,
+            lint_ms: 340,
+            total_ms: 500,
+        };
+        let response = create_response::<()>(StatusCode::OK, true, None, None, None).unwrap();
+        let response = with_server_timing(response, &timings);
+
+        let req = http::Request::builder()
+            .uri("/api/refresh")
+            .header(ORIGIN, "https://app.example")
+            .body(Body::Empty)
+            .unwrap();
+        let response = apply_cors(&req, response);
+        assert_eq!(
+            response.headers()[SERVER_TIMING],
+            "clone;dur=120, lint;dur=340, total;dur=500"
+        );
+        assert_eq!(response.headers()[TIMING_ALLOW_ORIGIN], "https://app.example");
+    }
+}
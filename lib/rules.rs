@@ -0,0 +1,213 @@
+use std::path::PathBuf;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tempfile::TempDir;
+use tokio::fs;
+use tokio::sync::OnceCell;
+use crate::config::cangjie_home;
+use crate::error::RefreshError;
+use crate::models::{AnalyzerInfo, AppliedRuleConfig, DefectLevel};
+use crate::repository::{resolve_repo_file, validate_subdir};
+use crate::utils::create_temp_dir;
+
+// 规则配置的大小上限，内联配置和仓库中的配置文件都受此限制
+const MAX_RULE_CONFIG_BYTES: usize = 64 * 1024;
+
+// 配置目录中的规则列表文件名，与cjlint自带的配置同名
+const RULE_CONFIG_FILE: &str = "cjlint_rule_list.json";
+
+/// 请求中指定的cjlint规则配置
+#[derive(Debug, Clone)]
+pub enum RuleConfig {
+    /// POST请求体中内联的配置
+    Inline(String),
+    /// 仓库中的配置文件，相对仓库根目录
+    RepoFile(PathBuf),
+}
+
+impl RuleConfig {
+    /// 根据请求中的内联配置或配置文件路径构造规则配置，两者只能指定一个
+    pub fn from_request(
+        inline: Option<&Value>,
+        path: Option<&str>,
+    ) -> Result<Option<Self>, RefreshError> {
+        match (inline, path) {
+            (None, None) => Ok(None),
+            (Some(_), Some(_)) => Err(RefreshError::InvalidParameter(
+                "Only one of rule_config and rule_config_path can be specified".to_string(),
+            )),
+            (Some(value), None) => {
+                let content = serde_json::to_string(value)?;
+                validate_rule_config(&content)?;
+                Ok(Some(RuleConfig::Inline(content)))
+            }
+            (None, Some(path)) => Ok(Some(RuleConfig::RepoFile(validate_subdir(path)?))),
+        }
+    }
+
+    /// 按提交缓存时区分不同规则配置的键后缀
+    ///
+    /// 仓库中的配置文件内容由提交决定，因此只需要区分路径
+    pub fn cache_suffix(&self) -> String {
+        match self {
+            RuleConfig::Inline(content) => format!("rules_{}", sha256_hex(content)),
+            RuleConfig::RepoFile(path) => format!("rules_path_{}", path.display()),
+        }
+    }
+
+    /// 在临时目录中准备传给cjlint的配置目录，目录随返回的 TempDir 一起删除
+    pub async fn prepare(
+        &self,
+        repo_path: &str,
+    ) -> Result<(TempDir, AppliedRuleConfig), RefreshError> {
+        let (content, source) = match self {
+            RuleConfig::Inline(content) => (content.clone(), "inline".to_string()),
+            RuleConfig::RepoFile(path) => {
+                let file = resolve_repo_file(repo_path, path)?;
+                if fs::metadata(&file).await?.len() > MAX_RULE_CONFIG_BYTES as u64 {
+                    return Err(rule_config_too_large());
+                }
+                let content = fs::read_to_string(&file).await.map_err(|e| {
+                    RefreshError::InvalidParameter(format!("Failed to read rule config: {}", e))
+                })?;
+                validate_rule_config(&content)?;
+                (content, path.to_string_lossy().to_string())
+            }
+        };
+
+        // 与cjlint的输出目录使用相同前缀，异常退出时由 sweep_stale_repos 清理
+        let config_dir = create_temp_dir("cjlint_")?;
+        fs::write(config_dir.path().join(RULE_CONFIG_FILE), &content).await?;
+
+        let applied = AppliedRuleConfig {
+            source,
+            sha256: sha256_hex(&content),
+        };
+        Ok((config_dir, applied))
+    }
+}
+
+/// 校验规则配置的大小和格式，配置必须是JSON对象或数组
+fn validate_rule_config(content: &str) -> Result<(), RefreshError> {
+    if content.len() > MAX_RULE_CONFIG_BYTES {
+        return Err(rule_config_too_large());
+    }
+
+    match serde_json::from_str::<Value>(content) {
+        Ok(Value::Object(_)) | Ok(Value::Array(_)) => Ok(()),
+        Ok(_) => Err(RefreshError::InvalidParameter(
+            "rule config must be a JSON object or array".to_string(),
+        )),
+        Err(e) => Err(RefreshError::InvalidParameter(format!("Invalid rule config: {}", e))),
+    }
+}
+
+fn rule_config_too_large() -> RefreshError {
+    RefreshError::InvalidParameter(format!(
+        "rule config exceeds the limit of {} bytes",
+        MAX_RULE_CONFIG_BYTES
+    ))
+}
+
+// 同一进程内只读取一次工具链自带的规则列表
+static ANALYZERS: OnceCell<Vec<AnalyzerInfo>> = OnceCell::const_new();
+
+/// 读取工具链自带的规则列表，得到cjlint支持的全部检查器，结果按名称排序
+pub async fn available_analyzers() -> Result<&'static [AnalyzerInfo], RefreshError> {
+    let analyzers = ANALYZERS
+        .get_or_try_init(|| async {
+            let manifest = cangjie_home().join("tools/config").join(RULE_CONFIG_FILE);
+            let content = fs::read_to_string(&manifest).await?;
+            parse_rule_list(&content)
+        })
+        .await?;
+    Ok(analyzers)
+}
+
+/// 解析规则列表，兼容以下结构：
+/// - `{"RuleList": ["G.FMT.01", ...]}`
+/// - 直接的规则名数组 `["G.FMT.01", ...]`
+///
+/// 规则中的对象元素取 `name` 或 `rule` 字段。默认级别按规范的编号约定推断：
+/// `P.` 开头的原则为强制，`G.` 开头的规则为建议，其他规则级别未知
+fn parse_rule_list(content: &str) -> Result<Vec<AnalyzerInfo>, RefreshError> {
+    let value: Value = serde_json::from_str(content)?;
+    let rules = match &value {
+        Value::Array(rules) => rules,
+        Value::Object(object) => match object.get("RuleList") {
+            Some(Value::Array(rules)) => rules,
+            _ => {
+                return Err(RefreshError::CjlintOutputInvalid(
+                    "rule list has no RuleList array".to_string(),
+                ));
+            }
+        },
+        _ => {
+            return Err(RefreshError::CjlintOutputInvalid(
+                "rule list is not a JSON object or array".to_string(),
+            ));
+        }
+    };
+
+    let mut analyzers: Vec<AnalyzerInfo> = rules
+        .iter()
+        .filter_map(|rule| match rule {
+            Value::String(name) => Some(name.as_str()),
+            Value::Object(object) => object
+                .get("name")
+                .or_else(|| object.get("rule"))
+                .and_then(Value::as_str),
+            _ => None,
+        })
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| AnalyzerInfo {
+            name: name.to_string(),
+            default_level: if name.starts_with("P.") {
+                Some(DefectLevel::Mandatory)
+            } else if name.starts_with("G.") {
+                Some(DefectLevel::Suggestions)
+            } else {
+                None
+            },
+        })
+        .collect();
+    analyzers.sort_by(|a, b| a.name.cmp(&b.name));
+    analyzers.dedup_by(|a, b| a.name == b.name);
+
+    Ok(analyzers)
+}
+
+fn sha256_hex(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rule_list_accepts_both_shapes_and_infers_levels() {
+        let names = |analyzers: Vec<AnalyzerInfo>| -> Vec<(String, Option<DefectLevel>)> {
+            analyzers.into_iter().map(|a| (a.name, a.default_level)).collect()
+        };
+        let expected = vec![
+            ("CUSTOM.01".to_string(), None),
+            ("G.FMT.01".to_string(), Some(DefectLevel::Suggestions)),
+            ("P.ERR.01".to_string(), Some(DefectLevel::Mandatory)),
+        ];
+
+        let wrapped =
+            r#"{"RuleList": ["P.ERR.01", {"name": "G.FMT.01"}, "CUSTOM.01", "P.ERR.01"]}"#;
+        assert_eq!(names(parse_rule_list(wrapped).unwrap()), expected);
+        let bare = r#"[" G.FMT.01 ", {"rule": "P.ERR.01"}, "CUSTOM.01", "", 3]"#;
+        assert_eq!(names(parse_rule_list(bare).unwrap()), expected);
+
+        for content in [r#"{"rules": []}"#, "\"G.FMT.01\""] {
+            assert!(matches!(
+                parse_rule_list(content),
+                Err(RefreshError::CjlintOutputInvalid(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,596 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::sync::{Arc, Mutex as StdMutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use glob::Pattern;
+use serde_json::json;
+use tokio::sync::{Mutex, MutexGuard, OnceCell};
+use crate::models::{AnalysisResult, AnalysisResultItem, DefectLevel};
+use crate::storage::set_redis_client;
+
+// 修改环境变量的测试共用的锁，避免并行运行时互相干扰
+static ENV_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// 在测试期间设置的环境变量，丢弃时恢复原值并释放锁
+pub struct EnvGuard {
+    previous: Vec<(String, Option<String>)>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+/// 持有环境变量锁并设置给定的变量，值为 None 时删除该变量
+pub async fn set_env(vars: &[(&str, Option<&str>)]) -> EnvGuard {
+    let lock = ENV_LOCK.lock().await;
+    let previous = vars
+        .iter()
+        .map(|(name, value)| {
+            let previous = env::var(name).ok();
+            match value {
+                Some(value) => env::set_var(name, value),
+                None => env::remove_var(name),
+            }
+            (name.to_string(), previous)
+        })
+        .collect();
+    EnvGuard {
+        previous,
+        _lock: lock,
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        for (name, value) in self.previous.drain(..).rev() {
+            match value {
+                Some(value) => env::set_var(&name, value),
+                None => env::remove_var(&name),
+            }
+        }
+    }
+}
+
+/// 构造一个问题，其余字段使用默认值
+pub fn item(file: &str, line: i32, analyzer: &str, level: DefectLevel) -> AnalysisResultItem {
+    AnalysisResultItem {
+        file: file.to_string(),
+        absolute_path: None,
+        line,
+        column: 1,
+        end_line: line,
+        end_column: 10,
+        analyzer_name: analyzer.to_string(),
+        description: format!("{} finding", analyzer),
+        defect_level: level,
+        original_defect_level: None,
+        defect_type: "TYPE".to_string(),
+        language: "Cangjie".to_string(),
+        package: None,
+        count: None,
+        snippet: None,
+    }
+}
+
+/// 构造只包含给定问题的分析结果，其余字段与旧的缓存数据一样使用默认值
+pub fn result(items: Vec<AnalysisResultItem>) -> AnalysisResult {
+    let mut analysis_result: AnalysisResult = serde_json::from_value(json!({
+        "cjlint": [],
+        "created_at": 0,
+        "commit": "0123456789abcdef0123456789abcdef01234567",
+        "package_name": "demo",
+    }))
+    .unwrap();
+    analysis_result.cjlint = items;
+    analysis_result
+}
+
+// 模拟cjlint：把同目录下的 report.json 写到 -o 指定的文件，其中的 {dir} 替换为 -f 指定的目录；
+// 同目录下存在 delay 时按其中的秒数等待，等待期间有其他实例在运行则记录到 overlaps
+const FAKE_CJLINT: &str = r#"#!/bin/sh
+if [ "$1" = "--version" ]; then
+    echo "cjlint version 0.0.0-test"
+    exit 0
+fi
+while [ $# -gt 0 ]; do
+    case "$1" in
+        -f) dir="$2"; shift ;;
+        -o) out="$2"; shift ;;
+        -c) config="$2"; shift ;;
+    esac
+    shift
+done
+home="$(dirname "$0")"
+echo "$dir" >> "$home/calls"
+echo "$LD_LIBRARY_PATH" > "$home/library_path"
+if [ -f "$home/delay" ]; then
+    mkdir "$home/running" 2>/dev/null || echo "$dir" >> "$home/overlaps"
+    sleep "$(cat "$home/delay")"
+    rmdir "$home/running" 2>/dev/null
+fi
+if [ -f "$home/report.json" ]; then
+    sed "s#{dir}#$dir#g" "$home/report.json" > "$out"
+fi
+# 指定规则配置时只保留配置中列出的规则，报告需每行一个问题
+if [ -n "$config" ] && [ -f "$out" ]; then
+    rules="$(cat "$config/cjlint_rule_list.json")"
+    grep '"analyzerName"' "$out" | while read -r line; do
+        name="$(echo "$line" | sed 's/.*"analyzerName": *"\([^"]*\)".*/\1/')"
+        case "$rules" in *"\"$name\""*) echo "${line%,}" ;; esac
+    done | sed '1!s/^/,/' > "$out.filtered"
+    { echo "["; cat "$out.filtered"; echo "]"; } > "$out"
+fi
+echo "fake cjlint stderr: cannot read $dir/cjpm.toml, writing $out" >&2
+exit {code}
+"#;
+
+/// 在 `home` 下安装模拟的cjlint，运行时写出 `report` 并以 `exit_code` 退出，
+/// `report` 为 None 时不写出报告
+pub fn fake_cjlint(home: &Path, report: Option<&str>, exit_code: i32) {
+    let bin_dir = home.join("tools/bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let script = bin_dir.join("cjlint");
+    fs::write(
+        &script,
+        FAKE_CJLINT.replace("{code}", &exit_code.to_string()),
+    )
+    .unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    if let Some(report) = report {
+        fs::write(bin_dir.join("report.json"), report).unwrap();
+    }
+}
+
+/// 模拟的cjlint被调用时 -f 参数的列表
+pub fn fake_cjlint_calls(home: &Path) -> Vec<String> {
+    fs::read_to_string(home.join("tools/bin/calls"))
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// 模拟的cjlint最近一次运行时的 `LD_LIBRARY_PATH`
+pub fn fake_cjlint_library_path(home: &Path) -> String {
+    fs::read_to_string(home.join("tools/bin/library_path")).unwrap().trim_end().to_string()
+}
+
+/// 让模拟的cjlint每次运行等待 `seconds` 秒，用于观察并发
+pub fn delay_fake_cjlint(home: &Path, seconds: f32) {
+    fs::write(home.join("tools/bin/delay"), seconds.to_string()).unwrap();
+}
+
+/// 与其他实例同时运行过的模拟cjlint调用的 -f 参数
+pub fn fake_cjlint_overlaps(home: &Path) -> Vec<String> {
+    fs::read_to_string(home.join("tools/bin/overlaps"))
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// 在 `dir` 下写入文件，自动创建上级目录
+pub fn write_file(dir: &Path, path: &str, content: &str) {
+    let path = dir.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+/// 在仓库的工作目录中写入文件并提交到当前分支，返回新提交
+pub fn commit_file(repo: &git2::Repository, path: &str, content: &str) -> git2::Oid {
+    let workdir = repo.workdir().unwrap().to_path_buf();
+    write_file(&workdir, path, content);
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(path)).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = git2::Signature::now("test", "test@example.com").unwrap();
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &signature, &signature, path, &tree, &parents)
+        .unwrap()
+}
+
+/// 在 `dir` 中初始化仓库并提交一个文件，返回仓库和该提交
+pub fn init_git_repo(dir: &Path) -> (git2::Repository, git2::Oid) {
+    let repo = git2::Repository::init(dir).unwrap();
+    let commit = commit_file(&repo, "README.md", "demo\n");
+    (repo, commit)
+}
+
+/// 本地仓库的 file:// 地址
+pub fn file_url(dir: &Path) -> String {
+    url::Url::from_directory_path(dir).unwrap().to_string()
+}
+
+/// 接受连接但从不响应的服务器，模拟卡住的git远端；丢弃时关闭所有连接，卡住的克隆随之失败
+pub struct HangingServer {
+    // 指向该服务器的 http 仓库地址
+    pub url: String,
+    pub port: u16,
+    // 已关闭时不再保留新连接
+    connections: Arc<StdMutex<(bool, Vec<TcpStream>)>>,
+}
+
+pub fn hanging_server() -> HangingServer {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let url = format!("http://127.0.0.1:{}/repo.git", port);
+    let connections = Arc::new(StdMutex::new((false, Vec::new())));
+    let server_connections = connections.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let mut connections = server_connections.lock().unwrap();
+            if connections.0 {
+                break;
+            }
+            connections.1.push(stream);
+        }
+    });
+    HangingServer {
+        url,
+        port,
+        connections,
+    }
+}
+
+impl Drop for HangingServer {
+    fn drop(&mut self) {
+        let mut connections = self.connections.lock().unwrap();
+        connections.0 = true;
+        connections.1.clear();
+    }
+}
+
+// 测试仓库所在的目录，全局git配置通过 insteadOf 把 https://fixture.test/ 指向这里
+static FIXTURE_ROOT: OnceLock<tempfile::TempDir> = OnceLock::new();
+
+/// 在 `https://fixture.test/<name>` 创建一个提交了 README 的仓库，返回仓库和地址
+///
+/// 接口只接受 https 地址，这里通过libgit2全局配置中的 `url.<base>.insteadOf` 把地址改写为本地目录；
+/// 使用这些地址的测试需要把 fixture.test 加入 ALLOWED_REPO_HOSTS，并且克隆完整历史
+pub fn fixture_repo(name: &str) -> (git2::Repository, String) {
+    let root = FIXTURE_ROOT.get_or_init(|| {
+        let root = tempfile::tempdir().unwrap();
+        let config_dir = root.path().join(".config");
+        fs::create_dir_all(&config_dir).unwrap();
+        let mut config = git2::Config::open(&config_dir.join(".gitconfig")).unwrap();
+        let base = format!("url.{}.insteadOf", file_url(root.path()));
+        config.set_str(&base, "https://fixture.test/").unwrap();
+        // SAFETY: 只在第一次创建测试仓库时设置一次，此后不再修改
+        unsafe { git2::opts::set_search_path(git2::ConfigLevel::Global, &config_dir).unwrap() };
+        root
+    });
+    let (repo, _) = init_git_repo(&root.path().join(name));
+    (repo, format!("https://fixture.test/{}", name))
+}
+
+/// 在 `https://fixture.test/<name>` 创建包含一个带源文件的包 demo 的仓库，返回仓库地址
+pub fn fixture_package(name: &str) -> String {
+    let (repo, url) = fixture_repo(name);
+    commit_file(&repo, "cjpm.toml", "[package]\nname = \"demo\"\n");
+    commit_file(&repo, "src/main.cj", "main() {\n    println(1)\n}\n");
+    url
+}
+
+// Redis中的值，只实现测试用到的类型
+enum RedisValue {
+    String(String),
+    List(VecDeque<String>),
+    Set(BTreeSet<String>),
+}
+
+#[derive(Default)]
+struct RedisStore {
+    values: HashMap<String, RedisValue>,
+    expires: HashMap<String, Instant>,
+}
+
+impl RedisStore {
+    fn purge_expired(&mut self) {
+        let now = Instant::now();
+        let expired: Vec<String> = self
+            .expires
+            .iter()
+            .filter(|(_, at)| **at <= now)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in expired {
+            self.values.remove(&key);
+            self.expires.remove(&key);
+        }
+    }
+
+    fn remove(&mut self, key: &str) -> bool {
+        self.expires.remove(key);
+        self.values.remove(key).is_some()
+    }
+
+    fn string(&self, key: &str) -> Option<String> {
+        match self.values.get(key) {
+            Some(RedisValue::String(value)) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
+
+// RESP协议的回复
+enum Reply {
+    Status(&'static str),
+    Error(String),
+    Integer(i64),
+    Bulk(Option<String>),
+    Array(Vec<Reply>),
+}
+
+impl Reply {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Reply::Status(status) => out.extend_from_slice(format!("+{}\r\n", status).as_bytes()),
+            Reply::Error(message) => out.extend_from_slice(format!("-{}\r\n", message).as_bytes()),
+            Reply::Integer(value) => out.extend_from_slice(format!(":{}\r\n", value).as_bytes()),
+            Reply::Bulk(None) => out.extend_from_slice(b"$-1\r\n"),
+            Reply::Bulk(Some(value)) => {
+                out.extend_from_slice(format!("${}\r\n{}\r\n", value.len(), value).as_bytes())
+            }
+            Reply::Array(items) => {
+                out.extend_from_slice(format!("*{}\r\n", items.len()).as_bytes());
+                for item in items {
+                    item.encode(out);
+                }
+            }
+        }
+    }
+}
+
+/// 在独立线程中运行的内存Redis，只支持本项目用到的命令
+pub struct FakeRedis {
+    store: Arc<StdMutex<RedisStore>>,
+}
+
+static FAKE_REDIS: OnceCell<FakeRedis> = OnceCell::const_new();
+
+/// 启动进程内共享的模拟Redis并让存储层的客户端连接它，各测试应使用互不相同的键
+pub async fn fake_redis() -> &'static FakeRedis {
+    FAKE_REDIS
+        .get_or_init(|| async {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("redis://{}", listener.local_addr().unwrap());
+            let store = Arc::new(StdMutex::new(RedisStore::default()));
+            let server_store = store.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let store = server_store.clone();
+                    thread::spawn(move || serve_redis(stream, store));
+                }
+            });
+            set_redis_client(redis::Client::open(url).unwrap());
+            FakeRedis { store }
+        })
+        .await
+}
+
+impl FakeRedis {
+    /// 读取字符串值
+    pub fn get(&self, key: &str) -> Option<String> {
+        let mut store = self.store.lock().unwrap();
+        store.purge_expired();
+        store.string(key)
+    }
+
+    /// 所有未过期的键
+    pub fn keys(&self) -> Vec<String> {
+        let mut store = self.store.lock().unwrap();
+        store.purge_expired();
+        store.values.keys().cloned().collect()
+    }
+
+    /// 键的剩余过期时间，没有过期时间时为 None
+    pub fn ttl(&self, key: &str) -> Option<Duration> {
+        let store = self.store.lock().unwrap();
+        store
+            .expires
+            .get(key)
+            .map(|at| at.saturating_duration_since(Instant::now()))
+    }
+}
+
+fn serve_redis(stream: TcpStream, store: Arc<StdMutex<RedisStore>>) {
+    let mut writer = stream.try_clone().unwrap();
+    let mut reader = BufReader::new(stream);
+    let mut queued: Option<Vec<Vec<String>>> = None;
+    while let Some(args) = read_command(&mut reader) {
+        let name = args[0].to_uppercase();
+        let reply = match (name.as_str(), queued.as_mut()) {
+            ("MULTI", _) => {
+                queued = Some(Vec::new());
+                Reply::Status("OK")
+            }
+            ("EXEC", Some(_)) => {
+                let commands = queued.take().unwrap();
+                let mut store = store.lock().unwrap();
+                Reply::Array(
+                    commands
+                        .iter()
+                        .map(|args| execute(&mut store, args))
+                        .collect(),
+                )
+            }
+            (_, Some(commands)) => {
+                commands.push(args);
+                Reply::Status("QUEUED")
+            }
+            (_, None) => execute(&mut store.lock().unwrap(), &args),
+        };
+        let mut out = Vec::new();
+        reply.encode(&mut out);
+        if writer.write_all(&out).is_err() {
+            return;
+        }
+    }
+}
+
+fn read_command(reader: &mut BufReader<TcpStream>) -> Option<Vec<String>> {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok().filter(|read| *read > 0)?;
+    let count: usize = line.trim_end().strip_prefix('*')?.parse().ok()?;
+    let mut args = Vec::with_capacity(count);
+    for _ in 0..count {
+        line.clear();
+        reader.read_line(&mut line).ok()?;
+        let len: usize = line.trim_end().strip_prefix('$')?.parse().ok()?;
+        let mut buf = vec![0; len + 2];
+        reader.read_exact(&mut buf).ok()?;
+        buf.truncate(len);
+        args.push(String::from_utf8(buf).ok()?);
+    }
+    Some(args)
+}
+
+fn execute(store: &mut RedisStore, args: &[String]) -> Reply {
+    store.purge_expired();
+    let arg = |index: usize| args.get(index).cloned().unwrap_or_default();
+    let int = |index: usize| arg(index).parse::<i64>().unwrap_or_default();
+    match args[0].to_uppercase().as_str() {
+        "PING" => Reply::Status("PONG"),
+        "GET" => Reply::Bulk(store.string(&arg(1))),
+        "MGET" => Reply::Array(
+            args[1..]
+                .iter()
+                .map(|key| Reply::Bulk(store.string(key)))
+                .collect(),
+        ),
+        "SET" | "SETEX" => {
+            let (key, value, seconds) = match args[0].to_uppercase().as_str() {
+                "SETEX" => (arg(1), arg(3), Some(int(2))),
+                _ => (
+                    arg(1),
+                    arg(2),
+                    args.iter()
+                        .position(|a| a.eq_ignore_ascii_case("EX"))
+                        .map(|i| int(i + 1)),
+                ),
+            };
+            store.remove(&key);
+            store.values.insert(key.clone(), RedisValue::String(value));
+            if let Some(seconds) = seconds {
+                store
+                    .expires
+                    .insert(key, Instant::now() + Duration::from_secs(seconds as u64));
+            }
+            Reply::Status("OK")
+        }
+        "INCR" | "INCRBY" => {
+            let key = arg(1);
+            let delta = if args.len() > 2 { int(2) } else { 1 };
+            let value = store
+                .string(&key)
+                .and_then(|v| v.parse::<i64>().ok())
+                .unwrap_or(0)
+                + delta;
+            store
+                .values
+                .insert(key, RedisValue::String(value.to_string()));
+            Reply::Integer(value)
+        }
+        "EXPIRE" => {
+            let key = arg(1);
+            if !store.values.contains_key(&key) {
+                return Reply::Integer(0);
+            }
+            store
+                .expires
+                .insert(key, Instant::now() + Duration::from_secs(int(2) as u64));
+            Reply::Integer(1)
+        }
+        "TTL" => {
+            let key = arg(1);
+            match (store.values.contains_key(&key), store.expires.get(&key)) {
+                (false, _) => Reply::Integer(-2),
+                (true, None) => Reply::Integer(-1),
+                (true, Some(at)) => Reply::Integer(
+                    at.saturating_duration_since(Instant::now())
+                        .as_secs_f64()
+                        .ceil() as i64,
+                ),
+            }
+        }
+        "DEL" => Reply::Integer(args[1..].iter().filter(|key| store.remove(key)).count() as i64),
+        "LPUSH" => {
+            let entry = store
+                .values
+                .entry(arg(1))
+                .or_insert_with(|| RedisValue::List(VecDeque::new()));
+            let RedisValue::List(list) = entry else {
+                return Reply::Error("WRONGTYPE".to_string());
+            };
+            for value in &args[2..] {
+                list.push_front(value.clone());
+            }
+            Reply::Integer(list.len() as i64)
+        }
+        "LTRIM" | "LRANGE" => {
+            let list = match store.values.get_mut(&arg(1)) {
+                Some(RedisValue::List(list)) => list,
+                _ if args[0].eq_ignore_ascii_case("LTRIM") => return Reply::Status("OK"),
+                _ => return Reply::Array(Vec::new()),
+            };
+            let len = list.len() as i64;
+            let bound = |index: i64| if index < 0 { len + index } else { index };
+            let start = bound(int(2)).max(0);
+            let stop = bound(int(3)).min(len - 1);
+            let range: Vec<String> = if start > stop {
+                Vec::new()
+            } else {
+                list.iter()
+                    .skip(start as usize)
+                    .take((stop - start + 1) as usize)
+                    .cloned()
+                    .collect()
+            };
+            if args[0].eq_ignore_ascii_case("LRANGE") {
+                return Reply::Array(range.into_iter().map(|v| Reply::Bulk(Some(v))).collect());
+            }
+            *list = range.into();
+            Reply::Status("OK")
+        }
+        "SADD" => {
+            let entry = store
+                .values
+                .entry(arg(1))
+                .or_insert_with(|| RedisValue::Set(BTreeSet::new()));
+            let RedisValue::Set(set) = entry else {
+                return Reply::Error("WRONGTYPE".to_string());
+            };
+            Reply::Integer(
+                args[2..]
+                    .iter()
+                    .filter(|m| set.insert(m.to_string()))
+                    .count() as i64,
+            )
+        }
+        "SMEMBERS" => match store.values.get(&arg(1)) {
+            Some(RedisValue::Set(set)) => {
+                Reply::Array(set.iter().map(|m| Reply::Bulk(Some(m.clone()))).collect())
+            }
+            _ => Reply::Array(Vec::new()),
+        },
+        "SCAN" => {
+            let pattern = args
+                .iter()
+                .position(|a| a.eq_ignore_ascii_case("MATCH"))
+                .and_then(|i| Pattern::new(&arg(i + 1)).ok());
+            let keys = store
+                .values
+                .keys()
+                .filter(|key| pattern.as_ref().is_none_or(|pattern| pattern.matches(key)))
+                .map(|key| Reply::Bulk(Some(key.clone())))
+                .collect();
+            Reply::Array(vec![Reply::Bulk(Some("0".to_string())), Reply::Array(keys)])
+        }
+        _ => Reply::Status("OK"),
+    }
+}
@@ -2,6 +2,7 @@ import { generateBadge } from '@/lib/badge';
 import { redis } from '@/lib/db';
 import { calculateScore } from '@/lib/grading';
 import { type AnalysisResponse } from '@/lib/types';
+import { repoKey } from '@/lib/utils';
 import { NextRequest, NextResponse } from 'next/server';
 
 export async function GET(request: NextRequest) {
@@ -16,7 +17,7 @@ export async function GET(request: NextRequest) {
   }
 
   try {
-    const analysisResponse = await redis.get<AnalysisResponse>(`cjlint_${repo}`);
+    const analysisResponse = await redis.get<AnalysisResponse>(`cjlint_${repoKey(repo)}`);
     
     if (!analysisResponse) {
       return NextResponse.json(
@@ -6,6 +6,7 @@ import { CopyMarkdownButton } from "@/components/ui/copy-markdown-button"
 import { redis } from "@/lib/db"
 import { calculateScore, DefectLevelColor, DefectLevelText, type DefectLevel, GRADE_DEFINITIONS } from "@/lib/grading"
 import { formatRelativeTime, type AnalysisResponse, type AnalysisResult } from "@/lib/types"
+import { repoKey } from "@/lib/utils"
 import { Folder, Clipboard } from "lucide-react"
 import Link from "next/link"
 
@@ -15,7 +16,7 @@ export default async function ResultPage({
   searchParams: Promise<{ repo: string }>
 }) {
   const repo = (await searchParams).repo
-  const analysisResponse = await redis.get<AnalysisResponse>(`cjlint_${repo}`)
+  const analysisResponse = await redis.get<AnalysisResponse>(`cjlint_${repoKey(repo)}`)
   
   if (!analysisResponse) {
     return <AnalysisLoading repo={repo} />
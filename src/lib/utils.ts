@@ -4,3 +4,18 @@ import { twMerge } from "tailwind-merge"
 export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs))
 }
+
+// 仓库结果在Redis中的键使用的规范化地址，规则与 lib/utils.rs 中的 normalize_repo_key 保持一致
+export function repoKey(repo: string): string {
+  const trimmed = repo.trim()
+  try {
+    const url = new URL(trimmed)
+    let path = url.pathname.replace(/\/+$/, "")
+    if (path.endsWith(".git")) {
+      path = path.slice(0, -".git".length).replace(/\/+$/, "")
+    }
+    return `${url.protocol}//${url.host.toLowerCase()}${path}`
+  } catch {
+    return trimmed
+  }
+}